@@ -0,0 +1,186 @@
+//! Selection of the sampling method.
+
+use rand::Rng;
+
+use crate::combination::{binomial, unrank};
+use crate::{paranoid, select, Distribution, Standard, Word};
+
+/// A method of generating uniformly chosen values with a fixed number of set bits, usable as a
+/// distribution.
+///
+/// All methods except [`Algorithm::Table`] generate the same distribution and differ only in
+/// speed, which depends on the type and the number of set bits. [`Algorithm::Auto`] picks a
+/// method by built-in heuristics, and [`Algorithm::resolve`] tells which one.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::thread_rng;
+/// use rand_bits::{Algorithm, Distribution};
+///
+/// assert_eq!(Algorithm::Auto.resolve::<u64>(2), Algorithm::Floyd);
+/// assert_eq!(Algorithm::Auto.resolve::<u64>(32), Algorithm::Rejection);
+/// let x: u64 = Algorithm::Auto.sample(&mut thread_rng(), 32);
+/// assert_eq!(x.count_ones(), 32);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Algorithm {
+    /// Pick one of the other methods for every type and number of set bits.
+    Auto,
+    /// Look the value up in a table of all bytes with a fixed number of set bits, splitting wider
+    /// values into halves recursively, as the [`Standard`] distribution does.
+    ///
+    /// The number of set bits of every half is chosen uniformly among the possible ones rather
+    /// than weighted by the numbers of values, so only `u8` values are chosen uniformly.
+    Table,
+    /// Choose the positions of the set (or unset, whichever are fewer) bits with Floyd's
+    /// algorithm, which is fastest for sparse and dense values.
+    Floyd,
+    /// Draw random values until one has the right number of set bits, which is fastest close to
+    /// half density.
+    ///
+    /// Every attempt draws `T::BITS` random bits and succeeds with probability
+    /// `C(T::BITS, bits) / 2^T::BITS`, e.g. about 0.1 for 32 set bits of `u64` but about
+    /// `2^-115` for 2 set bits of `u128`. After 64 failed attempts the value is chosen with
+    /// [`Algorithm::Floyd`] instead, which keeps the distribution uniform and bounds the time.
+    Rejection,
+    /// Draw the rank of the value and convert it with the combinatorial number system.
+    Unrank,
+}
+
+impl Algorithm {
+    /// The number of attempts of rejection before falling back to Floyd's algorithm.
+    const MAX_REJECTIONS: u32 = 64;
+    /// The minimal acceptance probability for which [`Algorithm::Auto`] picks rejection.
+    const MIN_ACCEPTANCE: f64 = 0.0625;
+
+    /// Return the method used for values of `T` with `bits` set bits, i.e. `self` unless it is
+    /// [`Algorithm::Auto`].
+    pub fn resolve<T>(self, bits: u32) -> Self
+    where
+        T: Word,
+    {
+        if self != Self::Auto {
+            return self;
+        }
+        let sparse = bits.min(T::BITS.saturating_sub(bits));
+        let acceptance = binomial(T::BITS, bits) as f64 / 2f64.powi(T::BITS as i32);
+        if T::BITS == u8::BITS {
+            Self::Table
+        } else if sparse > T::BITS / 8 && acceptance >= Self::MIN_ACCEPTANCE {
+            Self::Rejection
+        } else {
+            Self::Floyd
+        }
+    }
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl<T> Distribution<T> for Algorithm
+where
+    T: Word,
+    Standard: Distribution<T>,
+{
+    /// Generate a random value of `T` with `bits` set bits with the method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    fn sample<R>(&self, rng: &mut R, bits: u32) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        assert!(bits <= T::BITS, "bits count out of range");
+        let mask = select::ones(T::BITS);
+        let value = match self.resolve::<T>(bits) {
+            Self::Auto => unreachable!(),
+            Self::Table => return Standard.sample(rng, bits),
+            Self::Floyd => select::sample(rng, mask, bits),
+            Self::Rejection => {
+                let mut attempts = (0..Self::MAX_REJECTIONS).map(|_| draw(rng, T::BITS));
+                let value = attempts.find(|value| value.count_ones() == bits);
+                // every value with `bits` set bits is equally likely either way
+                value.unwrap_or_else(|| select::sample(rng, mask, bits))
+            },
+            Self::Unrank => unrank(T::BITS, bits, rng.gen_range(0..binomial(T::BITS, bits))),
+        };
+        paranoid::weight("Algorithm", value, bits, mask);
+        T::from_u128(value)
+    }
+}
+
+/// Return `width` random bits, drawing as few words from `rng` as possible.
+fn draw<R>(rng: &mut R, width: u32) -> u128
+where
+    R: Rng + ?Sized,
+{
+    let value = match width {
+        0..=32 => u128::from(rng.next_u32()),
+        33..=64 => u128::from(rng.next_u64()),
+        _ => rng.gen(),
+    };
+    value & select::ones(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn algorithms() {
+        let mut rng = rand::thread_rng();
+        let algorithms = [
+            Algorithm::Auto,
+            Algorithm::Table,
+            Algorithm::Floyd,
+            Algorithm::Rejection,
+            Algorithm::Unrank,
+        ];
+        for algorithm in algorithms {
+            for i in 0..=u16::BITS {
+                let n: u16 = algorithm.sample(&mut rng, i);
+                assert_eq!(n.count_ones(), i);
+            }
+        }
+        for i in 0..=u128::BITS {
+            let n: u128 = Algorithm::Auto.sample(&mut rng, i);
+            assert_eq!(n.count_ones(), i);
+            assert_ne!(Algorithm::Auto.resolve::<u128>(i), Algorithm::Auto);
+        }
+    }
+
+    #[test]
+    fn rejection() {
+        let mut rng = rand::thread_rng();
+        // hopeless for rejection alone
+        for i in [0, 1, 2, 126, 127, 128] {
+            let n: u128 = Algorithm::Rejection.sample(&mut rng, i);
+            assert_eq!(n.count_ones(), i);
+        }
+        let mut counts = [0u32; 256];
+        for _ in 0..28_000 {
+            let n: u8 = Algorithm::Rejection.sample(&mut rng, 2);
+            counts[usize::from(n)] += 1;
+        }
+        // each of the 28 values about 1000 times
+        assert!(counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .all(|count| (850..1150).contains(count)));
+        assert_eq!(counts.iter().filter(|&&count| count > 0).count(), 28);
+    }
+
+    #[test]
+    fn auto() {
+        assert_eq!(Algorithm::Auto.resolve::<u8>(4), Algorithm::Table);
+        assert_eq!(Algorithm::Auto.resolve::<u128>(1), Algorithm::Floyd);
+        assert_eq!(Algorithm::Auto.resolve::<u128>(64), Algorithm::Rejection);
+        assert_eq!(Algorithm::Auto.resolve::<u128>(127), Algorithm::Floyd);
+        assert_eq!(Algorithm::Unrank.resolve::<u128>(64), Algorithm::Unrank);
+    }
+}
@@ -0,0 +1,63 @@
+//! Antithetic pairs of values and their bitwise complements.
+
+use rand::Rng;
+
+use crate::Word;
+
+/// A distribution wrapper pairing every value generated by the inner distribution with its
+/// bitwise complement, a standard variance reduction technique in Monte Carlo methods.
+///
+/// The complement of a value with `k` set bits has `T::BITS - k` set bits.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::{thread_rng, Rng};
+/// use rand_bits::{Antithetic, WeightSpectrum};
+///
+/// let spectrum = WeightSpectrum::new([0, 1, 1]).unwrap();
+/// let (x, y): (u16, u16) = thread_rng().sample(Antithetic(spectrum));
+/// assert_eq!(x, !y);
+/// assert!(y.count_ones() >= 14);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Antithetic<D>(pub D);
+
+impl<D, T> rand::distributions::Distribution<(T, T)> for Antithetic<D>
+where
+    D: rand::distributions::Distribution<T>,
+    T: Word,
+{
+    fn sample<R>(&self, rng: &mut R) -> (T, T)
+    where
+        R: Rng + ?Sized,
+    {
+        let value = self.0.sample(rng);
+        (value, complement(value))
+    }
+}
+
+/// Return the bitwise complement of `value`.
+pub(crate) fn complement<T>(value: T) -> T
+where
+    T: Word,
+{
+    T::from_u128(!value.into_u128())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WeightSpectrum;
+
+    #[test]
+    fn antithetic() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u32::BITS {
+            let spectrum = WeightSpectrum::new((0..=u32::BITS).map(|j| u32::from(i == j))).unwrap();
+            let (x, y): (u32, u32) = rng.sample(Antithetic(&spectrum));
+            assert_eq!(x.count_ones(), i);
+            assert_eq!(y, !x);
+        }
+    }
+}
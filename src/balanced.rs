@@ -0,0 +1,74 @@
+//! Batches of values with every bit position set equally often.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::select;
+
+/// Return `count` random `width`-bit values with `bits` set bits each, where every bit position
+/// is set either `floor(count * bits / width)` or `ceil(count * bits / width)` times.
+///
+/// The batch starts from a cyclic assignment of positions, which has the required column sums,
+/// then it is randomized by permuting the positions and applying swaps which keep every row and
+/// column sum unchanged.
+pub(crate) fn sample<R>(rng: &mut R, width: u32, bits: u32, count: usize) -> Vec<u128>
+where
+    R: Rng + ?Sized,
+{
+    assert!(bits <= width, "bits count out of range");
+
+    let mut positions: Vec<u32> = (0..width).collect();
+    positions.shuffle(rng);
+    let mut rows: Vec<u128> = (0..count)
+        .map(|row| {
+            (0..bits).fold(0, |value, i| {
+                let column = (row as u64 * u64::from(bits) + u64::from(i)) % u64::from(width);
+                value | 1 << positions[column as usize]
+            })
+        })
+        .collect();
+
+    if count >= 2 {
+        for _ in 0..(count * width as usize) {
+            let a = rng.gen_range(0..count);
+            let b = rng.gen_range(0..count);
+            let (only_a, only_b) = (rows[a] & !rows[b], rows[b] & !rows[a]);
+            if only_a != 0 && only_b != 0 {
+                let swap = select::sample(rng, only_a, 1) | select::sample(rng, only_b, 1);
+                rows[a] ^= swap;
+                rows[b] ^= swap;
+            }
+        }
+    }
+    rows.shuffle(rng);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns() {
+        let mut rng = rand::thread_rng();
+        for (width, bits, count) in [
+            (8, 3, 10),
+            (16, 0, 4),
+            (16, 16, 4),
+            (32, 5, 1),
+            (128, 7, 100),
+            (64, 1, 0),
+        ] {
+            let rows = sample(&mut rng, width, bits, count);
+            assert_eq!(rows.len(), count);
+            assert!(rows
+                .iter()
+                .all(|row| row.count_ones() == bits && row & !select::ones(width) == 0));
+            let floor = count as u32 * bits / width;
+            for position in 0..width {
+                let sum = rows.iter().filter(|&&row| row >> position & 1 == 1).count() as u32;
+                assert!(sum == floor || sum == floor + 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Values whose set bits (ones) cluster toward high or low positions.
+
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::{paranoid, select, Distribution, Word};
+
+/// A distribution generating values with an exact number of set bits, biased toward the high
+/// or the low positions.
+///
+/// Values are built by splitting the set bits between the high and the low half recursively.
+/// Every split follows Fisher's noncentral hypergeometric distribution: the number of set bits
+/// `h` in the high half is chosen with probability proportional to
+/// `C(half, h) * C(half, bits - h) * odds^h`. Odds greater than one move the set bits toward
+/// the high positions, odds less than one toward the low positions, and odds of one generate
+/// uniformly chosen values.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::thread_rng;
+/// use rand_bits::{Biased, Distribution};
+///
+/// let x: u64 = Biased::new(1000.0).sample(&mut thread_rng(), 8);
+/// assert_eq!(x.count_ones(), 8);
+/// assert!(x.leading_zeros() < 32);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Biased {
+    odds: f64,
+}
+
+impl Biased {
+    /// Create a new distribution with the given odds of a set bit going to the high half rather
+    /// than the low half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `odds` is not positive and finite.
+    pub fn new(odds: f64) -> Self {
+        assert!(odds.is_finite() && odds > 0.0, "invalid odds");
+        Self { odds }
+    }
+
+    /// Return the odds of a set bit going to the high half rather than the low half.
+    pub fn odds(&self) -> f64 {
+        self.odds
+    }
+
+    fn generate<R>(&self, rng: &mut R, width: u32, bits: u32) -> u128
+    where
+        R: Rng + ?Sized,
+    {
+        if bits == 0 || bits == width {
+            return crate::select::ones(bits);
+        }
+        let low_half = width / 2;
+        let high_half = width - low_half;
+        let high_bits = self.split(rng, high_half, low_half, bits);
+        let high = self.generate(rng, high_half, high_bits);
+        let low = self.generate(rng, low_half, bits - high_bits);
+        (high << low_half) | low
+    }
+
+    /// Return the number of set bits of the high half, drawn from the noncentral hypergeometric
+    /// distribution.
+    fn split<R>(&self, rng: &mut R, high_half: u32, low_half: u32, bits: u32) -> u32
+    where
+        R: Rng + ?Sized,
+    {
+        let min = bits.saturating_sub(low_half);
+        let max = bits.min(high_half);
+        // logarithms keep large odds from overflowing
+        let ln_odds = self.odds.ln();
+        let ln_weights: Vec<f64> = (min..=max)
+            .map(|h| {
+                let count = binomial(high_half, h) as f64 * binomial(low_half, bits - h) as f64;
+                count.ln() + f64::from(h) * ln_odds
+            })
+            .collect();
+        let largest = ln_weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = ln_weights.iter().map(|w| (w - largest).exp()).collect();
+        let mut point = rng.gen::<f64>() * weights.iter().sum::<f64>();
+        for (h, weight) in (min..=max).zip(weights) {
+            if point < weight {
+                return h;
+            }
+            point -= weight;
+        }
+        max
+    }
+}
+
+impl<T> Distribution<T> for Biased
+where
+    T: Word,
+{
+    /// Generate a random value of `T` with `bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    fn sample<R>(&self, rng: &mut R, bits: u32) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        assert!(bits <= T::BITS, "bits count out of range");
+        let value = self.generate(rng, T::BITS, bits);
+        paranoid::weight("Biased", value, bits, select::ones(T::BITS));
+        T::from_u128(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        let mut rng = rand::thread_rng();
+        for odds in [1e-9, 0.5, 1.0, 2.0, 1e300] {
+            let biased = Biased::new(odds);
+            for i in 0..=u128::BITS {
+                let n: u128 = biased.sample(&mut rng, i);
+                assert_eq!(n.count_ones(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn clustered() {
+        let mut rng = rand::thread_rng();
+        let high: u32 = (0..1000)
+            .map(|_| Distribution::<u32>::sample(&Biased::new(4.0), &mut rng, 8).leading_zeros())
+            .sum();
+        let low: u32 = (0..1000)
+            .map(|_| Distribution::<u32>::sample(&Biased::new(0.25), &mut rng, 8).leading_zeros())
+            .sum();
+        assert!(high < low);
+        let n: u16 = Biased::new(1e12).sample(&mut rng, 5);
+        assert_eq!(n, 0xF800);
+        let n: u16 = Biased::new(1e-12).sample(&mut rng, 5);
+        assert_eq!(n, 0x001F);
+    }
+}
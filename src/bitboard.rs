@@ -0,0 +1,133 @@
+//! Chess bitboards with a fixed number of occupied squares.
+//!
+//! Squares are numbered in the little-endian rank-file mapping, i.e. `a1` is the least
+//! significant bit and `h8` is the most significant one.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::{thread_rng, Rng};
+//! use rand_bits::bitboard::{Occupancy, FILES, RANKS};
+//!
+//! let mut rng = thread_rng();
+//! let pawns = rng.sample(Occupancy::new(8).within(!(RANKS[0] | RANKS[7])));
+//! let pieces = rng.sample(
+//!     Occupancy::new(4)
+//!         .within(RANKS[0] | FILES[0])
+//!         .excluding(pawns),
+//! );
+//! assert_eq!(pawns.count_ones(), 8);
+//! assert_eq!(pieces.count_ones(), 4);
+//! assert_eq!(pawns & pieces, 0);
+//! ```
+
+use rand::Rng;
+
+use crate::select;
+
+/// The masks of ranks `1` to `8`.
+pub const RANKS: [u64; 8] = [
+    0x0000_0000_0000_00FF,
+    0x0000_0000_0000_FF00,
+    0x0000_0000_00FF_0000,
+    0x0000_0000_FF00_0000,
+    0x0000_00FF_0000_0000,
+    0x0000_FF00_0000_0000,
+    0x00FF_0000_0000_0000,
+    0xFF00_0000_0000_0000,
+];
+
+/// The masks of files `a` to `h`.
+pub const FILES: [u64; 8] = [
+    0x0101_0101_0101_0101,
+    0x0202_0202_0202_0202,
+    0x0404_0404_0404_0404,
+    0x0808_0808_0808_0808,
+    0x1010_1010_1010_1010,
+    0x2020_2020_2020_2020,
+    0x4040_4040_4040_4040,
+    0x8080_8080_8080_8080,
+];
+
+/// A distribution of bitboards with a fixed number of occupied squares, uniform over all boards
+/// satisfying the restrictions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Occupancy {
+    squares: u32,
+    allowed: u64,
+}
+
+impl Occupancy {
+    /// Create a new distribution of bitboards with `squares` occupied squares anywhere on the
+    /// board.
+    pub fn new(squares: u32) -> Self {
+        Self {
+            squares,
+            allowed: u64::MAX,
+        }
+    }
+
+    /// Restrict occupied squares to the ones in `region`, e.g. a rank or file mask.
+    ///
+    /// Subsequent restrictions are combined, i.e. squares must lie in every region.
+    pub fn within(mut self, region: u64) -> Self {
+        self.allowed &= region;
+        self
+    }
+
+    /// Forbid the squares occupied on `board`, so the generated bitboards are disjoint from it.
+    pub fn excluding(mut self, board: u64) -> Self {
+        self.allowed &= !board;
+        self
+    }
+}
+
+impl rand::distributions::Distribution<u64> for Occupancy {
+    /// Generate a random bitboard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if less squares are allowed than required to be occupied.
+    fn sample<R>(&self, rng: &mut R) -> u64
+    where
+        R: Rng + ?Sized,
+    {
+        select::sample(rng, u128::from(self.allowed), self.squares) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anywhere() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u64::BITS {
+            let board = rng.sample(Occupancy::new(i));
+            assert_eq!(board.count_ones(), i);
+        }
+    }
+
+    #[test]
+    fn restricted() {
+        let mut rng = rand::thread_rng();
+        for rank in RANKS {
+            for file in FILES {
+                let region = rank | file;
+                for i in 0..region.count_ones() {
+                    let board = rng.sample(Occupancy::new(i).within(region).excluding(rank & file));
+                    assert_eq!(board.count_ones(), i);
+                    assert_eq!(board & !region, 0);
+                    assert_eq!(board & rank & file, 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn overfull() {
+        rand::thread_rng().sample(Occupancy::new(9).within(RANKS[0]));
+    }
+}
@@ -0,0 +1,24 @@
+//! Hardware-accelerated bit deposit with the BMI2 instruction set.
+
+use std::arch::x86_64::_pdep_u64;
+
+/// Scatter the lowest bits of `value` into the positions of the set bits of `mask` with the
+/// `pdep` instruction, or return `None` if the CPU does not support BMI2.
+pub(crate) fn deposit(value: u128, mask: u128) -> Option<u128> {
+    if is_x86_feature_detected!("bmi2") {
+        // SAFETY: BMI2 has been detected at runtime
+        #[allow(unsafe_code)]
+        Some(unsafe { deposit_unchecked(value, mask) })
+    } else {
+        None
+    }
+}
+
+#[allow(unsafe_code)]
+#[target_feature(enable = "bmi2")]
+unsafe fn deposit_unchecked(value: u128, mask: u128) -> u128 {
+    let (low_mask, high_mask) = (mask as u64, (mask >> 64) as u64);
+    let low = _pdep_u64(value as u64, low_mask);
+    let high = _pdep_u64((value >> low_mask.count_ones()) as u64, high_mask);
+    u128::from(high) << 64 | u128::from(low)
+}
@@ -0,0 +1,222 @@
+//! A total number of set bits (ones) split across fields with individual capacities.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::{thread_rng, Rng};
+//! use rand_bits::budget::{Budget, Field};
+//!
+//! // a record of a 4-bit flag field with at most one flag, a byte and a 32-bit word
+//! let fields = [
+//!     Field::new(4).max_bits(1),
+//!     Field::new(8),
+//!     Field::new(32).max_bits(10),
+//! ];
+//! let budget = Budget::new(&fields, 15).unwrap();
+//! let record: Vec<u128> = thread_rng().sample(&budget);
+//! assert!(record[0].count_ones() <= 1);
+//! assert!(record[2].count_ones() <= 10);
+//! assert_eq!(
+//!     record.iter().map(|field| field.count_ones()).sum::<u32>(),
+//!     15
+//! );
+//! ```
+
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::{paranoid, select, Error};
+
+/// A field of a record, with its width and the maximal number of set bits it may hold.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Field {
+    width: u32,
+    max_bits: u32,
+}
+
+impl Field {
+    /// Create a new field of `width` bits, which may have all of them set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is greater than 128.
+    pub fn new(width: u32) -> Self {
+        assert!(width <= u128::BITS, "field width out of range");
+        Self { width, max_bits: width }
+    }
+
+    /// Limit the number of set bits of the field to `max_bits`.
+    ///
+    /// Capacities greater than the width of the field have no effect.
+    pub fn max_bits(mut self, max_bits: u32) -> Self {
+        self.max_bits = max_bits.min(self.width);
+        self
+    }
+}
+
+/// A distribution generating records of fields with a fixed total number of set bits, uniformly
+/// over all records whose fields respect their capacities.
+///
+/// The numbers of set bits of the fields follow the multivariate hypergeometric distribution
+/// conditioned on the capacities. They are drawn field by field with the numbers of completions
+/// of the remaining fields, which are counted upfront as natural logarithms to stay in range
+/// however many fields there are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Budget {
+    fields: Vec<Field>,
+    bits: u32,
+    completions: Vec<Vec<f64>>,
+}
+
+impl Budget {
+    /// Create a new distribution of records of `fields` with `bits` set bits in total.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if the capacities of the fields sum to less than `bits`.
+    pub fn new(fields: &[Field], bits: u32) -> Result<Self, Error> {
+        let capacity: u64 = fields.iter().map(|field| u64::from(field.max_bits)).sum();
+        if u64::from(bits) > capacity {
+            return Err(Error::Infeasible);
+        }
+
+        // completions[i][b] is the logarithm of the number of ways to fill fields i.. with b bits
+        let mut completions = vec![vec![f64::NEG_INFINITY; bits as usize + 1]; fields.len() + 1];
+        completions[fields.len()][0] = 0.0;
+        for (i, field) in fields.iter().enumerate().rev() {
+            completions[i] = (0..=bits)
+                .map(|b| {
+                    let terms = (0..=field.max_bits.min(b))
+                        .map(|j| ln_binomial(field.width, j) + completions[i + 1][(b - j) as usize]);
+                    ln_sum(terms)
+                })
+                .collect();
+        }
+        Ok(Self {
+            fields: fields.to_vec(),
+            bits,
+            completions,
+        })
+    }
+
+    /// Return the numbers of set bits of the fields of a random record.
+    pub fn split<R>(&self, rng: &mut R) -> Vec<u32>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut left = self.bits;
+        let mut split = Vec::with_capacity(self.fields.len());
+        for (i, field) in self.fields.iter().enumerate() {
+            let candidates = 0..=field.max_bits.min(left);
+            let ln_weight = |j: u32| ln_binomial(field.width, j) + self.completions[i + 1][(left - j) as usize];
+            // scale by the largest weight, which is positive for every feasible budget
+            let largest = candidates.clone().map(ln_weight).fold(f64::NEG_INFINITY, f64::max);
+            let weight = |j: u32| (ln_weight(j) - largest).exp();
+            let total: f64 = candidates.clone().map(weight).sum();
+            let mut point = rng.gen::<f64>() * total;
+            let mut bits = 0;
+            for j in candidates {
+                // skip impossible counts even if rounding puts the point past the last one
+                if weight(j) > 0.0 {
+                    bits = j;
+                    point -= weight(j);
+                    if point < 0.0 {
+                        break;
+                    }
+                }
+            }
+            paranoid::constraint("Budget", bits <= field.max_bits, || {
+                format!("field with {} set bits over the capacity of {}", bits, field.max_bits)
+            });
+            split.push(bits);
+            left -= bits;
+        }
+        paranoid::constraint("Budget", left == 0, || {
+            format!("split {:?} leaves {} of {} set bits", split, left, self.bits)
+        });
+        split
+    }
+}
+
+/// Return the natural logarithm of the binomial coefficient `n` choose `k`.
+fn ln_binomial(n: u32, k: u32) -> f64 {
+    (binomial(n, k) as f64).ln()
+}
+
+/// Return the natural logarithm of the sum of the exponentials of `terms`.
+fn ln_sum<I>(terms: I) -> f64
+where
+    I: Iterator<Item = f64> + Clone,
+{
+    let largest = terms.clone().fold(f64::NEG_INFINITY, f64::max);
+    if largest == f64::NEG_INFINITY {
+        return largest;
+    }
+    largest + terms.map(|term| (term - largest).exp()).sum::<f64>().ln()
+}
+
+impl rand::distributions::Distribution<Vec<u128>> for Budget {
+    /// Generate a random record, the value of every field in its lowest bits.
+    fn sample<R>(&self, rng: &mut R) -> Vec<u128>
+    where
+        R: Rng + ?Sized,
+    {
+        self.split(rng)
+            .into_iter()
+            .zip(&self.fields)
+            .map(|(bits, field)| select::sample(rng, select::ones(field.width), bits))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps() {
+        let mut rng = rand::thread_rng();
+        let fields = [
+            Field::new(8).max_bits(2),
+            Field::new(128),
+            Field::new(3).max_bits(3),
+            Field::new(0),
+        ];
+        for bits in 0..=133 {
+            let budget = Budget::new(&fields, bits).unwrap();
+            let record: Vec<u128> = rng.sample(&budget);
+            assert_eq!(record.iter().map(|field| field.count_ones()).sum::<u32>(), bits);
+            assert!(record[0] < 256 && record[0].count_ones() <= 2);
+            assert!(record[2] < 8);
+            assert_eq!(record[3], 0);
+        }
+        assert_eq!(Budget::new(&fields, 134), Err(Error::Infeasible));
+    }
+
+    #[test]
+    fn hypergeometric() {
+        let mut rng = rand::thread_rng();
+        // without caps the first of two 8-bit fields holds on average half of the bits
+        let budget = Budget::new(&[Field::new(8), Field::new(8)], 4).unwrap();
+        let total: u32 = (0..10_000).map(|_| budget.split(&mut rng)[0]).sum();
+        assert!((19_000..21_000).contains(&total));
+        // with a cap of one bit, one of C(8, 1) * C(8, 3) = 448 and C(8, 4) = 70 records
+        let budget = Budget::new(&[Field::new(8).max_bits(1), Field::new(8)], 4).unwrap();
+        let ones = (0..10_000).filter(|_| budget.split(&mut rng)[0] == 1).count();
+        assert!((8_400..8_900).contains(&ones));
+    }
+
+    #[test]
+    fn many() {
+        let mut rng = rand::thread_rng();
+        let fields = vec![Field::new(64); 40];
+        // the counts of the extreme totals are vanishingly small next to the central ones
+        for bits in [0, 1, 2, 1280, 2558, 2559, 2560] {
+            let budget = Budget::new(&fields, bits).unwrap();
+            let split = budget.split(&mut rng);
+            assert_eq!(split.iter().sum::<u32>(), bits);
+            let record: Vec<u128> = rng.sample(&budget);
+            assert_eq!(record.iter().map(|field| field.count_ones()).sum::<u32>(), bits);
+        }
+    }
+}
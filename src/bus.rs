@@ -0,0 +1,141 @@
+//! Bus words made of lanes with individually chosen numbers of set bits (ones).
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::bus::Bus;
+//!
+//! // a 64-bit bus of 8 byte lanes with increasing densities
+//! let words: Vec<u64> = Bus::new(thread_rng(), &[0, 1, 2, 3, 4, 5, 6, 7])
+//!     .take(100)
+//!     .collect();
+//! for word in words {
+//!     for (lane, byte) in word.to_le_bytes().iter().enumerate() {
+//!         assert_eq!(byte.count_ones(), lane as u32);
+//!     }
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::{select, Word};
+
+/// An infinite sequence of bus words of `T`, split into equally wide lanes, where every lane
+/// of every word has its own number of set bits.
+///
+/// By default every lane occupies contiguous bits, lane `0` the lowest ones. In the interleaved
+/// layout bit `j` of lane `i` is bit `j * lanes + i` of the word instead, as when consecutive
+/// bits are striped across the lanes.
+#[derive(Clone, Debug)]
+pub struct Bus<R, T> {
+    rng: R,
+    lanes: Vec<(u128, u32)>,
+    phantom: PhantomData<T>,
+}
+
+impl<R, T> Bus<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    /// Create a new sequence of words of contiguous lanes, one lane per entry of `bits` with
+    /// that many set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of lanes does not divide the width of `T`, or any number of set
+    /// bits is greater than the width of a lane.
+    pub fn new(rng: R, bits: &[u32]) -> Self {
+        let width = Self::lane_width(bits);
+        let lanes = (0..bits.len() as u32)
+            .map(|lane| select::ones(width) << (lane * width))
+            .zip(bits.iter().copied())
+            .collect();
+        Self {
+            rng,
+            lanes,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new sequence of words of interleaved lanes, one lane per entry of `bits` with
+    /// that many set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of lanes does not divide the width of `T`, or any number of set
+    /// bits is greater than the width of a lane.
+    pub fn interleaved(rng: R, bits: &[u32]) -> Self {
+        let width = Self::lane_width(bits);
+        let count = bits.len() as u32;
+        let lanes = (0..count)
+            .map(|lane| (0..width).fold(0, |mask, j| mask | 1 << (j * count + lane)))
+            .zip(bits.iter().copied())
+            .collect();
+        Self {
+            rng,
+            lanes,
+            phantom: PhantomData,
+        }
+    }
+
+    fn lane_width(bits: &[u32]) -> u32 {
+        let count = bits.len() as u32;
+        assert!(count > 0 && T::BITS % count == 0, "lanes do not divide the word");
+        let width = T::BITS / count;
+        assert!(bits.iter().all(|&bits| bits <= width), "bits count out of range");
+        width
+    }
+}
+
+impl<R, T> Iterator for Bus<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut word = 0;
+        for &(mask, bits) in &self.lanes {
+            word |= select::sample(&mut self.rng, mask, bits);
+        }
+        Some(T::from_u128(word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contiguous() {
+        for word in Bus::<_, u128>::new(rand::thread_rng(), &[32, 0, 7, 16]).take(100) {
+            let lanes = [
+                word as u32,
+                (word >> 32) as u32,
+                (word >> 64) as u32,
+                (word >> 96) as u32,
+            ];
+            assert_eq!(lanes.map(u32::count_ones), [32, 0, 7, 16]);
+        }
+    }
+
+    #[test]
+    fn interleaved() {
+        for word in Bus::<_, u16>::interleaved(rand::thread_rng(), &[4, 0, 1, 2]).take(100) {
+            for (lane, bits) in [4, 0, 1, 2].into_iter().enumerate() {
+                assert_eq!((word & (0x1111 << lane)).count_ones(), bits);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "lanes do not divide the word")]
+    fn uneven() {
+        let _ = Bus::<_, u32>::new(rand::thread_rng(), &[1, 1, 1]);
+    }
+}
@@ -0,0 +1,516 @@
+//! The class of all values with a fixed number of set bits (ones).
+
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::combination::{binomial, count_below, unrank};
+use crate::{paranoid, select, sequential, Error, Word};
+
+/// A virtual collection of all values of `T` with a fixed number of set bits, ordered
+/// ascending.
+///
+/// The values are never materialized: indices are mapped to values and back using the
+/// combinatorial number system, so even classes with more than `usize::MAX` values can be
+/// accessed randomly.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::thread_rng;
+/// use rand_bits::WeightClass;
+///
+/// let class = WeightClass::<u8>::new(2);
+/// assert_eq!(class.len(), 28);
+/// assert_eq!(class.get(0), Some(0b11));
+/// assert_eq!(class.index_of(0b101), Some(1));
+/// assert!(!class.contains(0b111));
+/// assert_eq!(class.iter().last(), Some(0b1100_0000));
+///
+/// let x = class.choose(&mut thread_rng());
+/// assert_eq!(x.count_ones(), 2);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WeightClass<T> {
+    bits: u32,
+    len: u128,
+    phantom: PhantomData<T>,
+}
+
+impl<T> WeightClass<T>
+where
+    T: Word,
+{
+    /// Create the class of values with `bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= T::BITS, "bits count out of range");
+        Self {
+            bits,
+            len: binomial(T::BITS, bits),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Return the number of set bits of the values in the class.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Return the number of values in the class.
+    pub fn len(&self) -> u128 {
+        self.len
+    }
+
+    /// Return `true` if the class has no values, which never happens.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the value at `index`, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: u128) -> Option<T> {
+        if index < self.len {
+            Some(T::from_u128(unrank(T::BITS, self.bits, index)))
+        } else {
+            None
+        }
+    }
+
+    /// Return `true` if the class contains `value`.
+    pub fn contains(&self, value: T) -> bool {
+        value.count_ones() == self.bits
+    }
+
+    /// Return the index of `value`, or `None` if the class does not contain it.
+    pub fn index_of(&self, value: T) -> Option<u128> {
+        if self.contains(value) {
+            Some(count_below(value.into_u128(), self.bits))
+        } else {
+            None
+        }
+    }
+
+    /// Return an iterator over the values in ascending order.
+    pub fn iter(&self) -> Iter<T> {
+        Iter {
+            next: select::ones(self.bits),
+            remaining: self.len,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Return a uniformly chosen value of the class.
+    pub fn choose<R>(&self, rng: &mut R) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        T::from_u128(select::sample(rng, select::ones(T::BITS), self.bits))
+    }
+
+    /// Return a uniformly chosen value of the class together with its index.
+    ///
+    /// The index takes only `log2(self.len())` bits, so it can be stored instead of the value
+    /// and turned back into it with [`WeightClass::get`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::WeightClass;
+    ///
+    /// let class = WeightClass::<u64>::new(4);
+    /// let (x, rank) = class.choose_with_rank(&mut thread_rng());
+    /// assert_eq!(class.get(rank), Some(x));
+    /// ```
+    pub fn choose_with_rank<R>(&self, rng: &mut R) -> (T, u128)
+    where
+        R: Rng + ?Sized,
+    {
+        let rank = rng.gen_range(0..self.len);
+        (T::from_u128(unrank(T::BITS, self.bits, rank)), rank)
+    }
+
+    /// Return `amount` distinct values of the class chosen uniformly, in random order.
+    ///
+    /// If the class has less than `amount` values, all of them are returned. Indices are chosen
+    /// with Floyd's algorithm, so only the returned values are ever materialized.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::WeightClass;
+    ///
+    /// let values = WeightClass::<u64>::new(3).choose_multiple(&mut thread_rng(), 10);
+    /// assert_eq!(values.len(), 10);
+    /// ```
+    pub fn choose_multiple<R>(&self, rng: &mut R, amount: usize) -> Vec<T>
+    where
+        R: Rng + ?Sized,
+    {
+        let amount = (amount as u128).min(self.len);
+        let mut chosen = HashSet::with_capacity(amount as usize);
+        let mut values = Vec::with_capacity(amount as usize);
+        for j in (self.len - amount)..self.len {
+            let index = rng.gen_range(0..=j);
+            let index = if chosen.insert(index) {
+                index
+            } else {
+                chosen.insert(j);
+                j
+            };
+            values.push(T::from_u128(unrank(T::BITS, self.bits, index)));
+        }
+        values.shuffle(rng);
+        values
+    }
+
+    /// Return `amount` distinct values of the class chosen uniformly, in ascending order.
+    ///
+    /// If the class has less than `amount` values, all of them are returned. Indices are chosen
+    /// in a single pass with Vitter's sequential sampling, so no sorting or deduplication is
+    /// needed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::WeightClass;
+    ///
+    /// let keys = WeightClass::<u32>::new(12).choose_sorted(&mut thread_rng(), 100);
+    /// assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    /// ```
+    pub fn choose_sorted<R>(&self, rng: &mut R, amount: usize) -> Vec<T>
+    where
+        R: Rng + ?Sized,
+    {
+        sequential::sample(rng, self.len, amount as u128)
+            .into_iter()
+            .map(|index| T::from_u128(unrank(T::BITS, self.bits, index)))
+            .collect()
+    }
+
+    /// Return a sequence of `len` values of the class in nondecreasing order, uniformly over all
+    /// such sequences.
+    ///
+    /// Nondecreasing sequences correspond to sorted choices of `len` distinct indices among
+    /// `self.len() + len - 1`, which are drawn with sequential sampling.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::WeightClass;
+    ///
+    /// let keys = WeightClass::<u16>::new(1).choose_nondecreasing(&mut thread_rng(), 100);
+    /// assert_eq!(keys.len(), 100);
+    /// assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+    /// ```
+    pub fn choose_nondecreasing<R>(&self, rng: &mut R, len: usize) -> Vec<T>
+    where
+        R: Rng + ?Sized,
+    {
+        if len == 0 {
+            return Vec::new();
+        }
+        sequential::sample(rng, self.len + len as u128 - 1, len as u128)
+            .into_iter()
+            .enumerate()
+            .map(|(i, index)| T::from_u128(unrank(T::BITS, self.bits, index - i as u128)))
+            .collect()
+    }
+
+    /// Return the class without the given forbidden values.
+    ///
+    /// Values not contained in the class are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::WeightClass;
+    ///
+    /// let class = WeightClass::<u8>::new(7).excluding([0x7F, 0xFE]);
+    /// assert_eq!(class.len(), 6);
+    /// let x = class.choose(&mut thread_rng()).unwrap();
+    /// assert!(x != 0x7F && x != 0xFE);
+    /// ```
+    pub fn excluding<I>(&self, values: I) -> Excluding<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.excluding_ranks(values.into_iter().filter_map(|value| self.index_of(value)))
+    }
+
+    /// Return the class without the values at the given forbidden indices.
+    ///
+    /// Indices out of bounds are ignored.
+    pub fn excluding_ranks<I>(&self, ranks: I) -> Excluding<T>
+    where
+        I: IntoIterator<Item = u128>,
+    {
+        let mut forbidden: Vec<u128> = ranks.into_iter().filter(|&rank| rank < self.len).collect();
+        forbidden.sort_unstable();
+        forbidden.dedup();
+        Excluding {
+            class: *self,
+            forbidden,
+        }
+    }
+}
+
+impl<T> rand::distributions::Distribution<T> for WeightClass<T>
+where
+    T: Word,
+{
+    /// Generate a uniformly chosen value of the class.
+    fn sample<R>(&self, rng: &mut R) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        self.choose(rng)
+    }
+}
+
+impl<T> IntoIterator for &WeightClass<T>
+where
+    T: Word,
+{
+    type IntoIter = Iter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Iter<T> {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a [`WeightClass`] in ascending order.
+#[derive(Clone, Debug)]
+pub struct Iter<T> {
+    next: u128,
+    remaining: u128,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Iterator for Iter<T>
+where
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.next;
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            // Gosper's hack
+            let lowest = value & value.wrapping_neg();
+            let ripple = value + lowest;
+            self.next = (((value ^ ripple) >> 2) / lowest) | ripple;
+        }
+        Some(T::from_u128(value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+/// A [`WeightClass`] without some forbidden values, ordered ascending.
+///
+/// Forbidden values are skipped in the index space instead of being rejected after sampling, so
+/// sampling never retries, even when almost every value of the class is forbidden.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Excluding<T> {
+    class: WeightClass<T>,
+    forbidden: Vec<u128>,
+}
+
+impl<T> Excluding<T>
+where
+    T: Word,
+{
+    /// Return the number of values left in the class.
+    pub fn len(&self) -> u128 {
+        self.class.len() - self.forbidden.len() as u128
+    }
+
+    /// Return `true` if every value of the class is forbidden.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the value at `index` among the values left, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: u128) -> Option<T> {
+        if index < self.len() {
+            self.class.get(self.skip(index))
+        } else {
+            None
+        }
+    }
+
+    /// Return `true` if the class contains `value` and it is not forbidden.
+    pub fn contains(&self, value: T) -> bool {
+        match self.class.index_of(value) {
+            Some(rank) => self.forbidden.binary_search(&rank).is_err(),
+            None => false,
+        }
+    }
+
+    /// Return a uniformly chosen value left in the class.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if every value of the class is forbidden.
+    pub fn choose<R>(&self, rng: &mut R) -> Result<T, Error>
+    where
+        R: Rng + ?Sized,
+    {
+        if self.is_empty() {
+            return Err(Error::Infeasible);
+        }
+        let index = rng.gen_range(0..self.len());
+        let rank = self.skip(index);
+        paranoid::constraint("Excluding", self.forbidden.binary_search(&rank).is_err(), || {
+            format!("forbidden rank {} chosen", rank)
+        });
+        let value = unrank(T::BITS, self.class.bits(), rank);
+        paranoid::weight("Excluding", value, self.class.bits(), select::ones(T::BITS));
+        Ok(T::from_u128(value))
+    }
+
+    /// Map an index among the values left to the index in the whole class.
+    fn skip(&self, index: u128) -> u128 {
+        // every forbidden rank not above the result shifts it by one
+        let mut rank = index;
+        for &forbidden in &self.forbidden {
+            if forbidden > rank {
+                break;
+            }
+            rank += 1;
+        }
+        rank
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class() {
+        for i in 0..=u8::BITS {
+            let class = WeightClass::<u8>::new(i);
+            let values: Vec<u8> = (0..=u8::MAX).filter(|n| n.count_ones() == i).collect();
+            assert_eq!(class.len(), values.len() as u128);
+            assert_eq!(class.iter().collect::<Vec<_>>(), values);
+            for (index, &value) in values.iter().enumerate() {
+                assert_eq!(class.get(index as u128), Some(value));
+                assert_eq!(class.index_of(value), Some(index as u128));
+            }
+            assert_eq!(class.get(class.len()), None);
+            for n in 0..=u8::MAX {
+                assert_eq!(class.contains(n), values.contains(&n));
+            }
+        }
+    }
+
+    #[test]
+    fn choose_multiple() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let class = WeightClass::<u8>::new(i);
+            for amount in [0, 1, 10, 100] {
+                let mut values = class.choose_multiple(&mut rng, amount);
+                assert_eq!(values.len() as u128, (amount as u128).min(class.len()));
+                assert!(values.iter().all(|&n| class.contains(n)));
+                values.sort_unstable();
+                values.dedup();
+                assert_eq!(values.len() as u128, (amount as u128).min(class.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn choose_sorted() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u128::BITS {
+            let class = WeightClass::<u128>::new(i);
+            let values = class.choose_sorted(&mut rng, 64);
+            assert_eq!(values.len() as u128, class.len().min(64));
+            assert!(values.iter().all(|&n| class.contains(n)));
+            assert!(values.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn choose_nondecreasing() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u16::BITS {
+            let class = WeightClass::<u16>::new(i);
+            for len in [0, 1, 50] {
+                let values = class.choose_nondecreasing(&mut rng, len);
+                assert_eq!(values.len(), len);
+                assert!(values.iter().all(|&n| class.contains(n)));
+                assert!(values.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn choose_with_rank() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u32::BITS {
+            let class = WeightClass::<u32>::new(i);
+            let (n, rank) = class.choose_with_rank(&mut rng);
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(class.index_of(n), Some(rank));
+        }
+    }
+
+    #[test]
+    fn excluding() {
+        let mut rng = rand::thread_rng();
+        let class = WeightClass::<u8>::new(4);
+        let forbidden: Vec<u8> = class.iter().step_by(3).collect();
+        let excluding = class.excluding(forbidden.iter().copied().chain([0xFF]));
+        let left: Vec<u8> = class.iter().filter(|n| !forbidden.contains(n)).collect();
+        assert_eq!(excluding.len(), left.len() as u128);
+        for (index, &value) in left.iter().enumerate() {
+            assert_eq!(excluding.get(index as u128), Some(value));
+        }
+        assert_eq!(excluding.get(excluding.len()), None);
+        for _ in 0..100 {
+            let n = excluding.choose(&mut rng).unwrap();
+            assert!(left.contains(&n));
+            assert!(excluding.contains(n));
+        }
+
+        let excluding = WeightClass::<u64>::new(64).excluding_ranks([0, 1]);
+        assert!(excluding.is_empty());
+        assert_eq!(excluding.choose(&mut rng), Err(Error::Infeasible));
+    }
+
+    #[test]
+    fn large() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u128::BITS {
+            let class = WeightClass::<u128>::new(i);
+            let last = class.get(class.len() - 1).unwrap();
+            assert_eq!(class.iter().next(), class.get(0));
+            assert_eq!(class.index_of(last), Some(class.len() - 1));
+            let n = class.choose(&mut rng);
+            assert_eq!(class.get(class.index_of(n).unwrap()), Some(n));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Combinatorics of values with a fixed number of set bits (ones).
+
+use std::cell::RefCell;
+
+thread_local! {
+    /// The rows of Pascal's triangle computed so far, extended on demand.
+    static PASCAL: RefCell<Vec<Vec<u128>>> = RefCell::new(vec![vec![1]]);
+}
+
+/// Return the binomial coefficient `n` choose `k`, i.e. the number of `n`-bit values with `k`
+/// set bits.
+///
+/// All coefficients with `n <= 128` fit in `u128`. Coefficients are looked up in a lazily
+/// extended per-thread Pascal's triangle, so every row is computed only once.
+pub(crate) fn binomial(n: u32, k: u32) -> u128 {
+    if k > n {
+        return 0;
+    }
+    PASCAL.with(|pascal| {
+        let mut pascal = pascal.borrow_mut();
+        while pascal.len() <= n as usize {
+            let last = pascal.last().unwrap();
+            let mut row = Vec::with_capacity(last.len() + 1);
+            row.push(1);
+            row.extend(last.windows(2).map(|w| w[0] + w[1]));
+            row.push(1);
+            pascal.push(row);
+        }
+        pascal[n as usize][k as usize]
+    })
+}
+
+/// Return the number of values with `bits` set bits less than `value`.
+///
+/// For values with `bits` set bits this is their rank, i.e. their index among all such values in
+/// ascending order.
+pub(crate) fn count_below(value: u128, bits: u32) -> u128 {
+    let mut count = 0;
+    let mut remaining = bits;
+    let mut value = value;
+    while value != 0 {
+        let position = u128::BITS - 1 - value.leading_zeros();
+        // values sharing the higher bits with `value` but with `position` unset
+        count += binomial(position, remaining);
+        if remaining == 0 {
+            break;
+        }
+        remaining -= 1;
+        value ^= 1 << position;
+    }
+    count
+}
+
+/// Return the value with `bits` set bits of the given `rank`, i.e. the inverse of [`count_below`]
+/// for `width`-bit values.
+///
+/// The `rank` must be less than `binomial(width, bits)`.
+pub(crate) fn unrank(width: u32, bits: u32, mut rank: u128) -> u128 {
+    let mut value = 0;
+    let mut remaining = bits;
+    for position in (0..width).rev() {
+        if remaining == 0 {
+            break;
+        }
+        let count = binomial(position, remaining);
+        if rank >= count {
+            value |= 1 << position;
+            rank -= count;
+            remaining -= 1;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pascal() {
+        assert_eq!(binomial(8, 0), 1);
+        assert_eq!(binomial(8, 4), 70);
+        assert_eq!(binomial(8, 9), 0);
+        assert_eq!(binomial(128, 64), 23_951_146_041_928_082_866_135_587_776_380_551_750);
+        for n in 1..=128 {
+            for k in 1..n {
+                assert_eq!(binomial(n, k), binomial(n - 1, k - 1) + binomial(n - 1, k));
+            }
+        }
+
+        // every thread builds its own table
+        let other = std::thread::spawn(|| binomial(100, 50)).join().unwrap();
+        assert_eq!(other, binomial(100, 50));
+    }
+
+    #[test]
+    fn rank() {
+        for i in 0..=u8::BITS {
+            let values: Vec<u128> = (0..=u128::from(u8::MAX)).filter(|n| n.count_ones() == i).collect();
+            for (rank, &value) in values.iter().enumerate() {
+                assert_eq!(count_below(value, i), rank as u128);
+                assert_eq!(unrank(u8::BITS, i, rank as u128), value);
+            }
+            assert_eq!(count_below(256, i), values.len() as u128);
+        }
+        for i in 0..=u128::BITS {
+            assert_eq!(
+                count_below(u128::MAX, i),
+                binomial(u128::BITS, i) - u128::from(i == u128::BITS)
+            );
+            let largest = u128::MAX.checked_shl(u128::BITS - i).unwrap_or_default();
+            assert_eq!(unrank(u128::BITS, i, binomial(u128::BITS, i) - 1), largest);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Vectors with a fixed number of set bits (ones) and a given syndrome over GF(2).
+//!
+//! Vectors of any length are packed into `u64` words, lowest bit first.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::coset::Coset;
+//!
+//! // the parity-check matrix of the [7, 4] Hamming code
+//! let h = vec![vec![0b1010101], vec![0b1100110], vec![0b1111000]];
+//! let coset = Coset::new(7, h, &[0b011]).unwrap();
+//! let v = coset.sample(&mut thread_rng(), 2, 1000).unwrap();
+//! assert_eq!(v[0].count_ones(), 2);
+//! assert_eq!((v[0] & 0b1010101).count_ones() % 2, 1);
+//! assert_eq!((v[0] & 0b1100110).count_ones() % 2, 1);
+//! assert_eq!((v[0] & 0b1111000).count_ones() % 2, 0);
+//! ```
+
+use rand::seq::index;
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::gf2::{self, Solutions};
+use crate::{paranoid, Error};
+
+/// The coset of all vectors `v` with `v · Hᵀ = s` for a parity-check matrix `H` and a syndrome
+/// `s` over GF(2).
+///
+/// Sampling is exact: the free variables of the reduced system, which determine the solution,
+/// are drawn with a number of set bits that can lead to the requested total, and the draw is
+/// retried until the total matches. Retries are bounded by an attempt budget, since the
+/// requested vectors may be extremely rare in the coset.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Coset {
+    solutions: Solutions,
+}
+
+impl Coset {
+    /// Create the coset of vectors of `len` bits whose product with every row of the
+    /// parity-check matrix `h` is the bit of `syndrome` at the index of the row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if no vector has the syndrome.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a row has less than `len` bits or the syndrome has less bits than rows.
+    pub fn new(len: usize, h: Vec<Vec<u64>>, syndrome: &[u64]) -> Result<Self, Error> {
+        assert!(syndrome.len() >= gf2::words(h.len()), "syndrome too short");
+        let rhs = (0..h.len()).map(|i| gf2::get(syndrome, i)).collect();
+        let solutions = Solutions::new(len, h, rhs).ok_or(Error::Infeasible)?;
+        Ok(Self { solutions })
+    }
+
+    /// Return the number of bits of the vectors.
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    /// Return `true` if the vectors have no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the dimension of the coset, i.e. the binary logarithm of its number of vectors.
+    pub fn dimension(&self) -> usize {
+        self.solutions.free().len()
+    }
+
+    /// Return a uniformly chosen vector of the coset with `bits` set bits, retrying at most
+    /// `attempts` times.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if no number of set free variables can lead to `bits` set
+    /// bits, or [`Error::AttemptsExhausted`] if no vector with `bits` set bits was drawn within
+    /// `attempts` attempts.
+    pub fn sample<R>(&self, rng: &mut R, bits: usize, attempts: usize) -> Result<Vec<u64>, Error>
+    where
+        R: Rng + ?Sized,
+    {
+        let free = self.solutions.free();
+        let rank = self.solutions.rank();
+        // the pivot variables contribute at most `rank` set bits
+        let low = bits.saturating_sub(rank);
+        let high = bits.min(free.len());
+        if bits > self.len() || low > high {
+            return Err(Error::Infeasible);
+        }
+        // every vector is drawn with the same probability when the number of set free variables
+        // is drawn with probability proportional to the number of such assignments
+        let counts: Vec<f64> = (low..=high).map(|j| ln_binomial(free.len(), j)).collect();
+        let largest = counts.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = counts.iter().map(|count| (count - largest).exp()).collect();
+        let total: f64 = weights.iter().sum();
+
+        for _ in 0..attempts {
+            let mut point = rng.gen::<f64>() * total;
+            let j = (low..=high)
+                .zip(&weights)
+                .find(|(_, &weight)| {
+                    point -= weight;
+                    point < 0.0
+                })
+                .map_or(high, |(j, _)| j);
+            let mut vector = vec![0; gf2::words(self.len())];
+            for i in index::sample(rng, free.len(), j) {
+                gf2::flip(&mut vector, free[i]);
+            }
+            self.solutions.complete(&mut vector);
+            if vector.iter().map(|word| word.count_ones() as usize).sum::<usize>() == bits {
+                paranoid::constraint("Coset", self.solutions.solves(&vector), || {
+                    format!("vector {:x?} outside the coset", vector)
+                });
+                return Ok(vector);
+            }
+        }
+        Err(Error::AttemptsExhausted)
+    }
+}
+
+/// Return the natural logarithm of the binomial coefficient `n` choose `k`.
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    if n <= 128 {
+        return (binomial(n as u32, k as u32) as f64).ln();
+    }
+    let k = k.min(n - k);
+    (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).ln()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn hamming() {
+        let mut rng = rand::thread_rng();
+        let h = vec![vec![0b1010101], vec![0b1100110], vec![0b1111000]];
+        for syndrome in 0..8u64 {
+            let coset = Coset::new(7, h.clone(), &[syndrome]).unwrap();
+            assert_eq!(coset.dimension(), 4);
+            for bits in 0..=7 {
+                let expected: Vec<u64> = (0..128u64)
+                    .filter(|v| v.count_ones() as usize == bits)
+                    .filter(|v| (0..3).all(|i| ((h[i][0] & v).count_ones() & 1) as u64 == syndrome >> i & 1))
+                    .collect();
+                match coset.sample(&mut rng, bits, 1000) {
+                    Ok(v) => assert!(expected.contains(&v[0])),
+                    Err(_) => assert!(expected.is_empty()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn uniform() {
+        let mut rng = rand::thread_rng();
+        // a single parity check over 10 bits
+        let coset = Coset::new(10, vec![vec![0x3FF]], &[1]).unwrap();
+        let mut counts = HashMap::new();
+        for _ in 0..21_000 {
+            *counts.entry(coset.sample(&mut rng, 3, 100).unwrap()[0]).or_insert(0u32) += 1;
+        }
+        assert_eq!(counts.len(), 120);
+        assert!(counts.values().all(|&count| (100..=260).contains(&count)));
+    }
+
+    #[test]
+    fn long() {
+        let mut rng = rand::thread_rng();
+        let len = 300;
+        let h: Vec<Vec<u64>> = (0..20).map(|_| (0..5).map(|_| rng.gen()).collect()).collect();
+        let coset = Coset::new(len, h.clone(), &[0x12345]).unwrap();
+        let v = coset.sample(&mut rng, 150, 1000).unwrap();
+        assert_eq!(v.iter().map(|word| word.count_ones()).sum::<u32>(), 150);
+        for (i, row) in h.iter().enumerate() {
+            let parity = row.iter().zip(&v).map(|(a, b)| (a & b).count_ones()).sum::<u32>() & 1;
+            assert_eq!(u64::from(parity), 0x12345 >> i & 1);
+        }
+        assert_eq!(coset.sample(&mut rng, 301, 10), Err(Error::Infeasible));
+    }
+}
@@ -0,0 +1,292 @@
+//! Payloads with a fixed number of set bits (ones) and a chosen CRC.
+//!
+//! A CRC is an affine function of the payload bits over GF(2), so the payloads with a chosen CRC
+//! form a [`Coset`] sampled with an exact number of set bits. Like for any coset, numbers of set
+//! bits far from half of the payload may take many attempts, and generator polynomials divisible
+//! by `x + 1` fix the parity of the number of set bits for every CRC.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::crc::{Crc, Payload};
+//!
+//! let payload = Payload::new(Crc::CRC32, 32, 0xDEAD_BEEF).unwrap();
+//! let bytes = payload.sample(&mut thread_rng(), 128, 1000).unwrap();
+//! assert_eq!(bytes.len(), 32);
+//! assert_eq!(bytes.iter().map(|byte| byte.count_ones()).sum::<u32>(), 128);
+//! assert_eq!(Crc::CRC32.checksum(&bytes), 0xDEAD_BEEF);
+//! ```
+
+use rand::Rng;
+
+use crate::coset::Coset;
+use crate::{gf2, paranoid, Error};
+
+/// A CRC algorithm in the parameter model of the CRC catalogue.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Crc {
+    width: u32,
+    poly: u64,
+    init: u64,
+    reflect: bool,
+    xor_out: u64,
+}
+
+impl Crc {
+    /// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE, with the check value `0x29B1`.
+    pub const CRC16: Self = Self::new(16, 0x1021).init(0xFFFF);
+    /// CRC-32/ISO-HDLC, used by Ethernet and zlib, with the check value `0xCBF43926`.
+    pub const CRC32: Self = Self::new(32, 0x04C1_1DB7)
+        .init(0xFFFF_FFFF)
+        .reflect(true)
+        .xor_out(0xFFFF_FFFF);
+    /// CRC-8/SMBUS, with the check value `0xF4`.
+    pub const CRC8: Self = Self::new(8, 0x07);
+
+    /// Create a new `width`-bit CRC with the generator polynomial `poly`, without the highest
+    /// term, a zero initial value, no reflection and no final XOR.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or greater than 64.
+    pub const fn new(width: u32, poly: u64) -> Self {
+        assert!(width > 0 && width <= u64::BITS, "width out of range");
+        Self {
+            width,
+            poly: poly & mask(width),
+            init: 0,
+            reflect: false,
+            xor_out: 0,
+        }
+    }
+
+    /// Set the initial value of the register.
+    pub const fn init(mut self, init: u64) -> Self {
+        self.init = init & mask(self.width);
+        self
+    }
+
+    /// Set whether the input bytes and the result are reflected, i.e. processed lowest bit
+    /// first.
+    pub const fn reflect(mut self, reflect: bool) -> Self {
+        self.reflect = reflect;
+        self
+    }
+
+    /// Set the value XORed with the result.
+    pub const fn xor_out(mut self, xor_out: u64) -> Self {
+        self.xor_out = xor_out & mask(self.width);
+        self
+    }
+
+    /// Return the width of the CRC.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Return the CRC of `bytes`.
+    pub fn checksum(&self, bytes: &[u8]) -> u64 {
+        let mut register = self.init;
+        for &byte in bytes {
+            let byte = if self.reflect { byte.reverse_bits() } else { byte };
+            for i in (0..u8::BITS).rev() {
+                register = self.step(register, u64::from(byte >> i) & 1);
+            }
+        }
+        self.output(register) ^ self.xor_out
+    }
+
+    /// Return the register after shifting in the input `bit`.
+    fn step(&self, register: u64, bit: u64) -> u64 {
+        let feedback = (register >> (self.width - 1) ^ bit) & 1;
+        let register = (register << 1) & mask(self.width);
+        if feedback == 1 {
+            register ^ self.poly
+        } else {
+            register
+        }
+    }
+
+    /// Return the register as the result, before the final XOR.
+    fn output(&self, register: u64) -> u64 {
+        if self.reflect {
+            register.reverse_bits() >> (u64::BITS - self.width)
+        } else {
+            register
+        }
+    }
+
+    /// Return the bits of the result flipped by every bit of a payload of `len` bytes, bit `j`
+    /// being bit `j % 8` of byte `j / 8`.
+    ///
+    /// The CRC is linear in the payload apart from the constant of the initial value and the
+    /// final XOR, and a set bit followed by `s` more bits leaves the register shifted `s` times
+    /// from the polynomial, so the columns take a single shift per payload bit instead of a CRC.
+    fn columns(&self, len: usize) -> Vec<u64> {
+        let bits = len * u8::BITS as usize;
+        let mut register = self.poly;
+        let shifted: Vec<u64> = (0..bits)
+            .map(|_| {
+                let column = self.output(register);
+                register = self.step(register, 0);
+                column
+            })
+            .collect();
+        (0..bits)
+            .map(|j| {
+                let i = j % 8;
+                let position = j - i + if self.reflect { i } else { 7 - i };
+                shifted[bits - 1 - position]
+            })
+            .collect()
+    }
+}
+
+/// Fixed-length payloads with a chosen CRC.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payload {
+    crc: Crc,
+    len: usize,
+    checksum: u64,
+    coset: Coset,
+}
+
+impl Payload {
+    /// Create the payloads of `len` bytes whose CRC is `checksum`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if no payload of `len` bytes has the CRC, e.g. when the
+    /// payload is shorter than the CRC.
+    pub fn new(crc: Crc, len: usize, checksum: u64) -> Result<Self, Error> {
+        let bits = len * u8::BITS as usize;
+        // every payload bit flips the CRC bits of its column relative to the CRC of the zero
+        // payload
+        let zero = crc.checksum(&vec![0; len]);
+        let mut h = vec![vec![0; gf2::words(bits)]; crc.width as usize];
+        for (j, column) in crc.columns(len).into_iter().enumerate() {
+            for (r, row) in h.iter_mut().enumerate() {
+                if column >> r & 1 == 1 {
+                    gf2::flip(row, j);
+                }
+            }
+        }
+        let checksum = checksum & mask(crc.width);
+        let coset = Coset::new(bits, h, &[checksum ^ zero])?;
+        Ok(Self {
+            crc,
+            len,
+            checksum,
+            coset,
+        })
+    }
+
+    /// Return the number of bytes of the payloads.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the payloads have no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the binary logarithm of the number of payloads with the CRC.
+    pub fn dimension(&self) -> usize {
+        self.coset.dimension()
+    }
+
+    /// Return a uniformly chosen payload with the CRC and `bits` set bits, retrying at most
+    /// `attempts` times.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Coset::sample`].
+    pub fn sample<R>(&self, rng: &mut R, bits: usize, attempts: usize) -> Result<Vec<u8>, Error>
+    where
+        R: Rng + ?Sized,
+    {
+        let words = self.coset.sample(rng, bits, attempts)?;
+        let bytes: Vec<u8> = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(self.len)
+            .collect();
+        if cfg!(feature = "paranoid") {
+            let checksum = self.crc.checksum(&bytes);
+            paranoid::constraint("Payload", checksum == self.checksum, || {
+                format!(
+                    "payload {:x?} has the CRC {:#x} instead of {:#x}",
+                    bytes, checksum, self.checksum
+                )
+            });
+        }
+        Ok(bytes)
+    }
+}
+
+/// Return a value with the lowest `width` bits set.
+const fn mask(width: u32) -> u64 {
+    u64::MAX >> (u64::BITS - width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check() {
+        assert_eq!(Crc::CRC8.checksum(b"123456789"), 0xF4);
+        assert_eq!(Crc::CRC16.checksum(b"123456789"), 0x29B1);
+        assert_eq!(Crc::CRC32.checksum(b"123456789"), 0xCBF4_3926);
+        assert_eq!(Crc::new(3, 0b011).xor_out(0b111).checksum(b"123456789"), 0x4);
+        assert_eq!(
+            Crc::new(64, 0x42F0_E1EB_A9EA_3693).checksum(b"123456789"),
+            0x6C40_DF5F_0B49_7347
+        );
+    }
+
+    #[test]
+    fn columns() {
+        let crcs = [
+            Crc::CRC8,
+            Crc::CRC16,
+            Crc::CRC32,
+            Crc::new(5, 0b00101).init(0b10101).reflect(true),
+        ];
+        for crc in crcs {
+            let zero = crc.checksum(&[0; 5]);
+            for (j, column) in crc.columns(5).into_iter().enumerate() {
+                let mut payload = [0; 5];
+                payload[j / 8] = 1 << (j % 8);
+                assert_eq!(column, crc.checksum(&payload) ^ zero);
+            }
+        }
+    }
+
+    #[test]
+    fn payloads() {
+        let mut rng = rand::thread_rng();
+        for crc in [Crc::CRC8, Crc::CRC16, Crc::CRC32] {
+            let checksum = rng.gen::<u64>() & mask(crc.width());
+            let payload = Payload::new(crc, 16, checksum).unwrap();
+            assert_eq!(payload.dimension(), 128 - crc.width() as usize);
+            let mut half = 0;
+            for bits in [1, 10, 63, 64, 120, 127] {
+                match payload.sample(&mut rng, bits, 1000) {
+                    Ok(bytes) => {
+                        assert_eq!(bytes.iter().map(|byte| byte.count_ones()).sum::<u32>() as usize, bits);
+                        assert_eq!(crc.checksum(&bytes), checksum);
+                        half += usize::from(bits == 63 || bits == 64);
+                    },
+                    // extreme weights are rare and some polynomials fix the parity of weights
+                    Err(error) => assert_eq!(error, Error::AttemptsExhausted),
+                }
+            }
+            // one of the weights around half of the payload has the right parity
+            assert!(half >= 1);
+        }
+        assert_eq!(Payload::new(Crc::CRC32, 2, 0x1234_5678), Err(Error::Infeasible));
+    }
+}
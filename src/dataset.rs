@@ -0,0 +1,170 @@
+//! Labeled datasets for bit counting and parity tasks.
+//!
+//! Every record consists of a value and its label. The numbers of set bits are drawn uniformly
+//! from `0..=T::BITS`, so all labels are equally represented. The same seed always produces the
+//! same dataset with the same version of this crate; other versions may change the sampling
+//! algorithms and thus the dataset.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand_bits::dataset::{Dataset, Format, Label};
+//!
+//! let mut output = Vec::new();
+//! Dataset::new(42)
+//!     .format(Format::Csv)
+//!     .label(Label::Parity)
+//!     .write::<u16, _>(&mut output, 100)
+//!     .unwrap();
+//! assert_eq!(String::from_utf8(output).unwrap().lines().count(), 101);
+//! ```
+
+use std::io::{self, BufWriter, Write};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{Distribution, Standard, Word};
+
+/// The byte order of values in the [`Format::Binary`] layout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Endian {
+    /// Little-endian byte order.
+    Little,
+    /// Big-endian byte order.
+    Big,
+}
+
+/// The layout of written records.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// A `value,label` header followed by one decimal record per line.
+    Csv,
+    /// Fixed-size records: the value in `T::BITS / 8` bytes followed by a single label byte.
+    Binary(Endian),
+}
+
+/// The label assigned to every value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Label {
+    /// The number of set bits.
+    Popcount,
+    /// The parity of the number of set bits (`1` when odd).
+    Parity,
+}
+
+impl Label {
+    fn of<T>(self, value: T) -> u32
+    where
+        T: Word,
+    {
+        match self {
+            Self::Popcount => value.count_ones(),
+            Self::Parity => value.count_ones() & 1,
+        }
+    }
+}
+
+/// A reproducible generator of labeled datasets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Dataset {
+    seed: u64,
+    format: Format,
+    label: Label,
+}
+
+impl Dataset {
+    /// Create a new dataset generator with the given seed, writing [`Label::Popcount`] labels in
+    /// the [`Format::Csv`] layout.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            format: Format::Csv,
+            label: Label::Popcount,
+        }
+    }
+
+    /// Set the layout of written records.
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the label assigned to every value.
+    pub fn label(mut self, label: Label) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// Write `count` records with values of `T` into `writer`.
+    pub fn write<T, W>(&self, writer: W, count: usize) -> io::Result<()>
+    where
+        T: Word,
+        Standard: Distribution<T>,
+        W: Write,
+    {
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+        let mut writer = BufWriter::new(writer);
+        if self.format == Format::Csv {
+            writeln!(writer, "value,label")?;
+        }
+        for _ in 0..count {
+            let bits = rng.gen_range(0..=T::BITS);
+            let value: T = Standard.sample(&mut rng, bits);
+            let label = self.label.of(value);
+            match self.format {
+                Format::Csv => writeln!(writer, "{},{}", value.into_u128(), label)?,
+                Format::Binary(endian) => {
+                    let size = (T::BITS / 8) as usize;
+                    match endian {
+                        Endian::Little => writer.write_all(&value.into_u128().to_le_bytes()[..size])?,
+                        Endian::Big => writer.write_all(&value.into_u128().to_be_bytes()[(16 - size)..])?,
+                    }
+                    writer.write_all(&[label as u8])?;
+                },
+            }
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv() {
+        let mut output = Vec::new();
+        Dataset::new(0).write::<u32, _>(&mut output, 64).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("value,label"));
+        for line in lines {
+            let (value, label) = line.split_once(',').unwrap();
+            let value: u32 = value.parse().unwrap();
+            assert_eq!(value.count_ones(), label.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn binary() {
+        let mut output = Vec::new();
+        let dataset = Dataset::new(0).format(Format::Binary(Endian::Big)).label(Label::Parity);
+        dataset.write::<u16, _>(&mut output, 64).unwrap();
+        assert_eq!(output.len(), 64 * 3);
+        for record in output.chunks(3) {
+            let value = u16::from_be_bytes([record[0], record[1]]);
+            assert_eq!(value.count_ones() & 1, u32::from(record[2]));
+        }
+    }
+
+    #[test]
+    fn reproducible() {
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        let dataset = Dataset::new(7).format(Format::Binary(Endian::Little));
+        dataset.write::<u64, _>(&mut first, 64).unwrap();
+        dataset.write::<u64, _>(&mut second, 64).unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,116 @@
+//! Streams with an exact number of set bits (ones) in every epoch.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::epoch::Epochs;
+//!
+//! let words: Vec<u32> = Epochs::new(thread_rng(), 100, 800).take(1000).collect();
+//! for epoch in words.chunks(100) {
+//!     assert_eq!(epoch.iter().map(|word| word.count_ones()).sum::<u32>(), 800);
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::{hypergeometric, paranoid, select, Word};
+
+/// An infinite stream of values where every epoch of `len` consecutive values has exactly
+/// `ones` set bits in total.
+///
+/// Within an epoch the set bits are distributed uniformly over all positions, so the stream has
+/// exactly the target density over every epoch instead of drifting like independent values do.
+/// The number of set bits of every value is drawn from the hypergeometric distribution of the
+/// credit left in the epoch.
+#[derive(Clone, Debug)]
+pub struct Epochs<R, T> {
+    rng: R,
+    len: u128,
+    ones: u128,
+    remaining_len: u128,
+    remaining_ones: u128,
+    phantom: PhantomData<T>,
+}
+
+impl<R, T> Epochs<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    /// Create a new stream with epochs of `len` values with `ones` set bits in total.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero or `ones` is greater than the number of bits in an epoch.
+    pub fn new(rng: R, len: usize, ones: u128) -> Self {
+        assert!(len > 0, "epoch length is zero");
+        let len = len as u128;
+        assert!(ones <= len * u128::from(T::BITS), "bits count out of range");
+        Self {
+            rng,
+            len,
+            ones,
+            remaining_len: len,
+            remaining_ones: ones,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<R, T> Iterator for Epochs<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining_len == 0 {
+            self.remaining_len = self.len;
+            self.remaining_ones = self.ones;
+        }
+        let population = self.remaining_len * u128::from(T::BITS);
+        let bits = hypergeometric::sample(&mut self.rng, population, self.remaining_ones, T::BITS);
+        self.remaining_len -= 1;
+        self.remaining_ones -= u128::from(bits);
+        if self.remaining_len == 0 {
+            let left = self.remaining_ones;
+            paranoid::constraint("Epochs", left == 0, || {
+                format!("epoch ends with {} set bits left", left)
+            });
+        }
+        Some(T::from_u128(select::sample(&mut self.rng, select::ones(T::BITS), bits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        let rng = rand::thread_rng();
+        for ones in [0, 1, 7, 256, 511, 512] {
+            let words: Vec<u8> = Epochs::new(rng.clone(), 64, ones).take(64 * 5).collect();
+            for epoch in words.chunks(64) {
+                assert_eq!(
+                    epoch.iter().map(|word| u128::from(word.count_ones())).sum::<u128>(),
+                    ones
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spread() {
+        let mut counts = [0u32; 4];
+        for word in Epochs::<_, u16>::new(rand::thread_rng(), 4, 16).take(4 * 2000) {
+            counts[word.trailing_zeros().min(3) as usize] += 1;
+        }
+        // the first bit of every word is set with probability 1/4
+        assert!((1_700..2_300).contains(&counts[0]));
+    }
+}
@@ -0,0 +1,28 @@
+//! Error type of fallible generators.
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+/// An error returned when a value cannot be generated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// No value satisfies the requested constraints.
+    Infeasible,
+    /// No generated value was accepted within the allowed number of attempts.
+    AttemptsExhausted,
+    /// The number of set bits is greater than the width of the type.
+    BitsOutOfRange,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Infeasible => write!(f, "no value satisfies the constraints"),
+            Self::AttemptsExhausted => write!(f, "no value accepted within the allowed attempts"),
+            Self::BitsOutOfRange => write!(f, "bits count out of range"),
+        }
+    }
+}
+
+impl error::Error for Error {}
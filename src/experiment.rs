@@ -0,0 +1,135 @@
+//! Coverage and collision experiments on values with a fixed number of set bits (ones).
+//!
+//! Both experiments keep every generated value in memory, so they are meant for small widths
+//! (or numbers of set bits close to `0` or the width) where the number of possible values stays
+//! manageable. [`coverage`] refuses more than [`MAX_COVERAGE`] possible values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::experiment;
+//!
+//! let outcome = experiment::coverage::<u8, _>(&mut thread_rng(), 2);
+//! assert_eq!(outcome.distinct, 28);
+//! assert!(outcome.samples >= 28);
+//! ```
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::{Distribution, Standard, Word};
+
+/// The largest number of possible values [`coverage`] collects, which takes about 15 million
+/// samples on average.
+pub const MAX_COVERAGE: u128 = 1 << 20;
+
+/// The outcome of a single experiment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    /// The number of generated values, including the last one.
+    pub samples: u64,
+    /// The number of distinct values among the generated ones.
+    pub distinct: u64,
+    /// The time spent on the experiment.
+    pub elapsed: Duration,
+}
+
+/// Generate values of `T` with `bits` set bits until every possible value has been seen (the
+/// coupon collector's problem).
+///
+/// # Panics
+///
+/// Panics if `bits` is greater than the width of `T`, or there are more than [`MAX_COVERAGE`]
+/// possible values.
+pub fn coverage<T, R>(rng: &mut R, bits: u32) -> Outcome
+where
+    T: Word,
+    Standard: Distribution<T>,
+    R: Rng + ?Sized,
+{
+    assert!(bits <= T::BITS, "bits count out of range");
+    let size = binomial(T::BITS, bits);
+    assert!(size <= MAX_COVERAGE, "too many values to cover");
+
+    let start = Instant::now();
+    let mut seen = HashSet::new();
+    let mut samples = 0;
+    while (seen.len() as u128) < size {
+        let value: T = Standard.sample(rng, bits);
+        seen.insert(value);
+        samples += 1;
+    }
+    Outcome {
+        samples,
+        distinct: seen.len() as u64,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Generate values of `T` with `bits` set bits until the first repeated value (the birthday
+/// problem).
+///
+/// # Panics
+///
+/// Panics if `bits` is greater than the width of `T`.
+pub fn collision<T, R>(rng: &mut R, bits: u32) -> Outcome
+where
+    T: Word,
+    Standard: Distribution<T>,
+    R: Rng + ?Sized,
+{
+    assert!(bits <= T::BITS, "bits count out of range");
+
+    let start = Instant::now();
+    let mut seen = HashSet::new();
+    let mut samples = 0;
+    loop {
+        let value: T = Standard.sample(rng, bits);
+        samples += 1;
+        if !seen.insert(value) {
+            break;
+        }
+    }
+    Outcome {
+        samples,
+        distinct: seen.len() as u64,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let outcome = super::coverage::<u8, _>(&mut rng, i);
+            assert_eq!(outcome.distinct as u128, binomial(u8::BITS, i));
+            assert!(outcome.samples >= outcome.distinct);
+        }
+        // a single set bit of 128 positions is covered quickly
+        assert_eq!(super::coverage::<u128, _>(&mut rng, 1).distinct, 128);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many values to cover")]
+    fn uncoverable() {
+        super::coverage::<u128, _>(&mut rand::thread_rng(), 64);
+    }
+
+    #[test]
+    fn collision() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let outcome = super::collision::<u8, _>(&mut rng, i);
+            assert_eq!(outcome.samples, outcome.distinct + 1);
+            assert!(outcome.distinct as u128 <= binomial(u8::BITS, i));
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Fixed-size frames with header fields and a payload with a chosen number of set bits (ones).
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::frame::Frame;
+//!
+//! let frame = Frame::new(64)
+//!     .field(0, &[0xAA, 0xBB])
+//!     .field(2, &[0x00, 0x40])
+//!     .payload_bits(100..=120);
+//! let bytes = frame.generate(&mut thread_rng());
+//! assert_eq!(bytes.len(), 64);
+//! assert_eq!(bytes[..4], [0xAA, 0xBB, 0x00, 0x40]);
+//! let ones: u32 = bytes[4..].iter().map(|byte| byte.count_ones()).sum();
+//! assert!((100..=120).contains(&ones));
+//! ```
+
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::{hypergeometric, paranoid, Distribution, Standard};
+
+/// A template of fixed-size frames.
+///
+/// Header fields carry fixed bytes at fixed offsets, and all the remaining bytes form the
+/// payload. The total number of set bits of the payload is uniformly chosen from a range, and
+/// the set bits are spread uniformly over the whole payload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    len: usize,
+    fields: Vec<(usize, Vec<u8>)>,
+    bits: Option<RangeInclusive<u32>>,
+}
+
+impl Frame {
+    /// Create a new template of frames of `len` bytes, without header fields and with any number
+    /// of set bits in the payload.
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            fields: Vec::new(),
+            bits: None,
+        }
+    }
+
+    /// Add a header field carrying `bytes` at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field does not fit in the frame or overlaps another field.
+    pub fn field(mut self, offset: usize, bytes: &[u8]) -> Self {
+        let end = offset.checked_add(bytes.len()).filter(|&end| end <= self.len);
+        let end = end.expect("field out of range");
+        assert!(
+            self.fields
+                .iter()
+                .all(|(start, field)| end <= *start || start + field.len() <= offset),
+            "overlapping fields"
+        );
+        self.fields.push((offset, bytes.to_vec()));
+        self
+    }
+
+    /// Set the range the number of set bits of the payload is chosen from.
+    ///
+    /// Use `bits..=bits` for an exact number of set bits.
+    pub fn payload_bits(mut self, bits: RangeInclusive<u32>) -> Self {
+        self.bits = Some(bits);
+        self
+    }
+
+    /// Return the number of bytes of the payload.
+    pub fn payload_len(&self) -> usize {
+        self.len - self.fields.iter().map(|(_, field)| field.len()).sum::<usize>()
+    }
+
+    /// Generate a new frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of payload set bits is empty or exceeds the number of payload bits.
+    pub fn generate<R>(&self, rng: &mut R) -> Vec<u8>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut frame = vec![0; self.len];
+        let mut header = vec![false; self.len];
+        for (offset, field) in &self.fields {
+            frame[*offset..(*offset + field.len())].copy_from_slice(field);
+            header[*offset..(*offset + field.len())].fill(true);
+        }
+
+        let mut population = self.payload_len() as u128 * 8;
+        let mut ones = match &self.bits {
+            Some(bits) => {
+                assert!(
+                    !bits.is_empty() && u128::from(*bits.end()) <= population,
+                    "bits count out of range"
+                );
+                u128::from(rng.gen_range(bits.clone()))
+            },
+            None => rng.gen_range(0..=population),
+        };
+        for (byte, _) in frame.iter_mut().zip(header).filter(|(_, header)| !header) {
+            // spread the set bits over the bytes left like drawing bits without replacement
+            let bits = hypergeometric::sample(rng, population, ones, u8::BITS);
+            *byte = Standard.sample(rng, bits);
+            population -= u128::from(u8::BITS);
+            ones -= u128::from(bits);
+        }
+        paranoid::constraint("Frame", ones == 0, || format!("payload misses {} set bits", ones));
+        for (offset, field) in &self.fields {
+            let found = &frame[*offset..(*offset + field.len())];
+            paranoid::constraint("Frame", found == &field[..], || {
+                format!("field {:x?} at offset {} overwritten with {:x?}", field, offset, found)
+            });
+        }
+        frame
+    }
+
+    /// Generate `count` frames and write them into `writer`.
+    pub fn write<R, W>(&self, rng: &mut R, mut writer: W, count: usize) -> io::Result<()>
+    where
+        R: Rng + ?Sized,
+        W: Write,
+    {
+        for _ in 0..count {
+            writer.write_all(&self.generate(rng))?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        let mut rng = rand::thread_rng();
+        let frame = Frame::new(20).field(18, &[0xFF, 0xFF]).field(0, &[0x12]);
+        assert_eq!(frame.payload_len(), 17);
+        for i in [0, 1, 68, 135, 136] {
+            let bytes = frame.clone().payload_bits(i..=i).generate(&mut rng);
+            assert_eq!(bytes[0], 0x12);
+            assert_eq!(bytes[18..], [0xFF, 0xFF]);
+            assert_eq!(bytes[1..18].iter().map(|byte| byte.count_ones()).sum::<u32>(), i);
+        }
+    }
+
+    #[test]
+    fn write() {
+        let mut output = Vec::new();
+        let frame = Frame::new(16).field(0, &[0xAB]);
+        frame.write(&mut rand::thread_rng(), &mut output, 10).unwrap();
+        assert_eq!(output.len(), 160);
+        assert!(output.chunks(16).all(|frame| frame[0] == 0xAB));
+    }
+
+    #[test]
+    #[should_panic(expected = "overlapping fields")]
+    fn overlapping() {
+        let _ = Frame::new(16).field(2, &[0; 4]).field(5, &[0; 2]);
+    }
+}
@@ -0,0 +1,145 @@
+//! Linear systems over GF(2) with bit vectors packed into `u64` words, lowest bit first.
+
+/// Return the number of `u64` words holding `len` bits.
+pub(crate) fn words(len: usize) -> usize {
+    (len + 63) / 64
+}
+
+/// Return bit `index` of the packed vector.
+pub(crate) fn get(vector: &[u64], index: usize) -> bool {
+    vector[index / 64] >> (index % 64) & 1 == 1
+}
+
+/// Toggle bit `index` of the packed vector.
+pub(crate) fn flip(vector: &mut [u64], index: usize) {
+    vector[index / 64] ^= 1 << (index % 64);
+}
+
+/// The solutions of a linear system `H x = s` over GF(2), in reduced row echelon form.
+///
+/// Every solution is determined by its free variables: the pivot variable of every reduced
+/// row is the right-hand side plus the free variables the row contains.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Solutions {
+    len: usize,
+    rows: Vec<Vec<u64>>,
+    rhs: Vec<bool>,
+    pivots: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl Solutions {
+    /// Solve the system with the given rows of `len` variables each, or return `None` if it has
+    /// no solutions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the numbers of rows and right-hand sides differ, or a row is too short.
+    pub(crate) fn new(len: usize, mut rows: Vec<Vec<u64>>, mut rhs: Vec<bool>) -> Option<Self> {
+        assert_eq!(rows.len(), rhs.len(), "rows and right-hand sides differ");
+        assert!(rows.iter().all(|row| row.len() >= words(len)), "row too short");
+        for row in &mut rows {
+            row.truncate(words(len));
+            if len % 64 != 0 {
+                row[len / 64] &= (1 << (len % 64)) - 1;
+            }
+        }
+
+        // Gauss-Jordan elimination
+        let mut pivots = Vec::new();
+        let mut free = Vec::new();
+        for column in 0..len {
+            let rank = pivots.len();
+            match (rank..rows.len()).find(|&i| get(&rows[i], column)) {
+                Some(i) => {
+                    rows.swap(rank, i);
+                    rhs.swap(rank, i);
+                    let (pivot_row, pivot_rhs) = (rows[rank].clone(), rhs[rank]);
+                    for (i, (row, rhs)) in rows.iter_mut().zip(&mut rhs).enumerate() {
+                        if i != rank && get(row, column) {
+                            row.iter_mut().zip(&pivot_row).for_each(|(word, pivot)| *word ^= pivot);
+                            *rhs ^= pivot_rhs;
+                        }
+                    }
+                    pivots.push(column);
+                },
+                None => free.push(column),
+            }
+        }
+        // the remaining rows are zero, so their right-hand sides must be too
+        let rank = pivots.len();
+        if rhs[rank..].iter().any(|&rhs| rhs) {
+            return None;
+        }
+        rows.truncate(rank);
+        rhs.truncate(rank);
+        Some(Self {
+            len,
+            rows,
+            rhs,
+            pivots,
+            free,
+        })
+    }
+
+    /// Return the number of variables.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return the number of pivot variables, i.e. the rank of the system.
+    pub(crate) fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// Return the free variables, in ascending order.
+    pub(crate) fn free(&self) -> &[usize] {
+        &self.free
+    }
+
+    /// Return `true` if `vector` is a solution of the system.
+    pub(crate) fn solves(&self, vector: &[u64]) -> bool {
+        self.rows
+            .iter()
+            .zip(&self.rhs)
+            .all(|(row, &rhs)| parity(row, vector) == rhs)
+    }
+
+    /// Complete the assignment of the free variables in `vector`, whose pivot variables are
+    /// unset, into a solution by setting the pivot variables.
+    pub(crate) fn complete(&self, vector: &mut [u64]) {
+        for ((row, &rhs), &pivot) in self.rows.iter().zip(&self.rhs).zip(&self.pivots) {
+            if parity(row, vector) != rhs {
+                flip(vector, pivot);
+            }
+        }
+    }
+}
+
+/// Return the parity of the product of two packed vectors.
+fn parity(a: &[u64], b: &[u64]) -> bool {
+    a.iter().zip(b).map(|(a, b)| (a & b).count_ones()).sum::<u32>() & 1 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system() {
+        // x0 + x1 = 1, x1 + x2 = 0, x0 + x2 = 1
+        let rows = vec![vec![0b011], vec![0b110], vec![0b101]];
+        let solutions = Solutions::new(3, rows.clone(), vec![true, false, true]).unwrap();
+        assert_eq!(solutions.rank(), 2);
+        assert_eq!(solutions.free(), [2]);
+        for free in [0, 0b100] {
+            let mut vector = vec![free];
+            solutions.complete(&mut vector);
+            assert_eq!(vector[0] & 0b100, free);
+            for (row, rhs) in rows.iter().zip([true, false, true]) {
+                assert_eq!((row[0] & vector[0]).count_ones() & 1 == 1, rhs);
+            }
+        }
+        assert_eq!(Solutions::new(3, rows, vec![true, true, true]), None);
+    }
+}
@@ -0,0 +1,53 @@
+//! Non-cryptographic mixing functions with results stable across platforms.
+
+/// Advance the SplitMix64 `state` and return its next output.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    mix64(*state)
+}
+
+/// Return the SplitMix64 finalizer of `value`, a bijective mix of its bits.
+pub(crate) fn mix64(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// Return the high 128 bits of the 256-bit product of `a` and `b`.
+///
+/// With `a` uniform, the result is an index below `b` taking every value for either
+/// `floor(2^128 / b)` or `ceil(2^128 / b)` values of `a`, without the division of a modulo.
+pub(crate) fn mul_high(a: u128, b: u128) -> u128 {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    let low = a_low * b_low;
+    let cross = a_low * b_high;
+    let other = a_high * b_low;
+    // at most three 64-bit terms, so the sum cannot overflow
+    let middle = (low >> 64) + (cross & LOW) + (other & LOW);
+    a_high * b_high + (cross >> 64) + (other >> 64) + (middle >> 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn product() {
+        assert_eq!(mul_high(u128::MAX, u128::MAX), u128::MAX - 1);
+        assert_eq!(mul_high(1 << 127, 6), 3);
+        assert_eq!(mul_high(u128::MAX, 70), 69);
+        assert_eq!(mul_high(1 << 64, 1 << 64), 1);
+        let mut state = 0;
+        for _ in 0..1000 {
+            let a = u128::from(splitmix64(&mut state));
+            let b = u128::from(splitmix64(&mut state)) << 64 | u128::from(splitmix64(&mut state));
+            // with a below 2^64 the product fits into 192 bits
+            assert_eq!(
+                mul_high(a << 64, b),
+                (a * (b >> 64)) + ((a * (b & u128::from(u64::MAX))) >> 64)
+            );
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Sampling from the hypergeometric distribution.
+
+use rand::Rng;
+
+/// Return the number of successes among `draws` draws without replacement from a population of
+/// `population` items with `successes` successes.
+///
+/// Items are drawn one by one with exact integer probabilities, which takes `O(draws)` time.
+///
+/// # Panics
+///
+/// Panics if `successes` or `draws` is greater than `population`.
+pub(crate) fn sample<R>(rng: &mut R, mut population: u128, mut successes: u128, draws: u32) -> u32
+where
+    R: Rng + ?Sized,
+{
+    assert!(
+        successes <= population && u128::from(draws) <= population,
+        "population too small"
+    );
+    let mut count = 0;
+    for drawn in 0..draws {
+        if successes == 0 {
+            return count;
+        }
+        if successes == population {
+            return count + (draws - drawn);
+        }
+        if rng.gen_range(0..population) < successes {
+            count += 1;
+            successes -= 1;
+        }
+        population -= 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(sample(&mut rng, 10, 0, 5), 0);
+        assert_eq!(sample(&mut rng, 10, 10, 5), 5);
+        assert_eq!(sample(&mut rng, 10, 4, 10), 4);
+        let trials = 20_000;
+        let total: u32 = (0..trials).map(|_| sample(&mut rng, 100, 30, 20)).sum();
+        let mean = f64::from(total) / f64::from(trials);
+        assert!((mean - 6.0).abs() < 0.1);
+    }
+}
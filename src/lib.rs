@@ -15,17 +15,94 @@
 //! assert_eq!(z.count_ones(), 1);
 //! ```
 //!
+//! # Features
+//!
+//! - `bmi2` - scatter chosen bits into masked positions with the `pdep` instruction on x86-64
+//!   CPUs detected at runtime to support BMI2, falling back to the portable loop otherwise.
+//! - `oracle` - expose the `oracle` module with a deliberately simple reference sampler for
+//!   differential testing.
+//! - `paranoid` - re-check the number of set bits and the constraints of every generated value,
+//!   panicking with a detailed report on mismatch.
+//! - `test-util` - expose the `testing` module exhaustively verifying the uniformity of
+//!   generators of `u8` and `u16` values.
+//!
 //! # License
 //!
 //! This crate is licensed under the MIT License.
 
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "bmi2"), forbid(unsafe_code))]
+#![cfg_attr(feature = "bmi2", deny(unsafe_code))]
 
 use std::cmp::min;
+use std::ops::RangeInclusive;
 
 use phf::{phf_map, Map};
 use rand::Rng;
 
+pub use crate::algorithm::Algorithm;
+pub use crate::antithetic::Antithetic;
+pub use crate::biased::Biased;
+pub use crate::class::WeightClass;
+pub use crate::error::Error;
+pub use crate::mixture::Mixture;
+pub use crate::operands::Operands;
+pub use crate::policy::{Bits, Policy};
+pub use crate::spectrum::WeightSpectrum;
+pub use crate::symmetry::Symmetry;
+pub use crate::word::Word;
+
+mod algorithm;
+mod antithetic;
+mod balanced;
+mod biased;
+pub mod bitboard;
+#[cfg(all(feature = "bmi2", target_arch = "x86_64"))]
+mod bmi2;
+pub mod budget;
+pub mod bus;
+pub mod class;
+mod combination;
+pub mod coset;
+pub mod crc;
+pub mod dataset;
+pub mod epoch;
+mod error;
+pub mod experiment;
+pub mod frame;
+mod gf2;
+mod hash;
+mod hypergeometric;
+pub mod markov;
+mod mixture;
+pub mod mode;
+pub mod moments;
+pub mod monitor;
+mod operands;
+#[cfg(feature = "oracle")]
+pub mod oracle;
+mod paranoid;
+mod policy;
+pub mod quasi;
+pub mod replay;
+pub mod rll;
+pub mod schedule;
+mod select;
+mod sequential;
+pub mod signed;
+mod spectrum;
+pub mod stats;
+pub mod stratified;
+pub mod stream;
+pub mod switching;
+mod symmetry;
+#[cfg(feature = "test-util")]
+pub mod testing;
+pub mod unique;
+pub mod uuid;
+pub mod verify;
+pub mod window;
+mod word;
+
 const MAPPING: Map<u32, &'static [u8]> = phf_map! {
     1u32 => &[0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80],
     2u32 => &[0x03, 0x05, 0x06, 0x09, 0x0A, 0x0C, 0x11, 0x12, 0x14, 0x18, 0x21, 0x22, 0x24, 0x28, 0x30, 0x41, 0x42, 0x44, 0x48, 0x50, 0x60, 0x81, 0x82, 0x84, 0x88, 0x90, 0xA0, 0xC0],
@@ -40,6 +117,29 @@ const MAPPING: Map<u32, &'static [u8]> = phf_map! {
 /// Based on [`rand::distributions::Standard`].
 pub struct Standard;
 
+impl Standard {
+    /// Generate a random value of `T`, handling an out-of-range number of set bits according to
+    /// `policy`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BitsOutOfRange`] if `bits` is greater than the width of `T` and the
+    /// policy is [`Policy::Error`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T` and the policy is [`Policy::Panic`].
+    pub fn sample_with_policy<T, R>(&self, rng: &mut R, bits: u32, policy: Policy) -> Result<T, Error>
+    where
+        Self: Distribution<T>,
+        T: Word,
+        R: Rng + ?Sized,
+    {
+        let bits = policy.apply::<T>(bits)?;
+        Ok(self.sample(rng, bits))
+    }
+}
+
 /// Types (distributions) that can be used to create a random instance of `T`.
 ///
 /// Based on [`rand::distributions::Distribution`].
@@ -55,7 +155,7 @@ impl Distribution<u8> for Standard {
     where
         R: Rng + ?Sized,
     {
-        match bits {
+        let value = match bits {
             0 => u8::MIN,
             u8::BITS => u8::MAX,
             1..=4 => {
@@ -68,7 +168,9 @@ impl Distribution<u8> for Standard {
                 Distribution::<u8>::sample(self, rng, bits) ^ 0xFF
             },
             _ => panic!("bits count out of range"),
-        }
+        };
+        paranoid::weight("Standard", value.into_u128(), bits, select::ones(u8::BITS));
+        value
     }
 }
 
@@ -77,10 +179,11 @@ impl Distribution<u16> for Standard {
     where
         R: Rng + ?Sized,
     {
-        match bits {
+        let value = match bits {
             0 => u16::MIN,
             u16::BITS => u16::MAX,
             bits if (1..u16::BITS).contains(&bits) => {
+                #[allow(clippy::manual_saturating_arithmetic)]
                 let min_high_bits = bits.checked_sub(u8::BITS).unwrap_or_default();
                 let max_high_bits = min(bits, u8::BITS);
                 let high_bits = rng.gen_range(min_high_bits..=max_high_bits);
@@ -92,7 +195,9 @@ impl Distribution<u16> for Standard {
                 value
             },
             _ => panic!("bits count out of range"),
-        }
+        };
+        paranoid::weight("Standard", value.into_u128(), bits, select::ones(u16::BITS));
+        value
     }
 }
 
@@ -101,10 +206,11 @@ impl Distribution<u32> for Standard {
     where
         R: Rng + ?Sized,
     {
-        match bits {
+        let value = match bits {
             0 => u32::MIN,
             u32::BITS => u32::MAX,
             bits if (1..u32::BITS).contains(&bits) => {
+                #[allow(clippy::manual_saturating_arithmetic)]
                 let min_high_bits = bits.checked_sub(u16::BITS).unwrap_or_default();
                 let max_high_bits = min(bits, u16::BITS);
                 let high_bits = rng.gen_range(min_high_bits..=max_high_bits);
@@ -116,7 +222,9 @@ impl Distribution<u32> for Standard {
                 value
             },
             _ => panic!("bits count out of range"),
-        }
+        };
+        paranoid::weight("Standard", value.into_u128(), bits, select::ones(u32::BITS));
+        value
     }
 }
 
@@ -125,10 +233,11 @@ impl Distribution<u64> for Standard {
     where
         R: Rng + ?Sized,
     {
-        match bits {
+        let value = match bits {
             0 => u64::MIN,
             u64::BITS => u64::MAX,
             bits if (1..u64::BITS).contains(&bits) => {
+                #[allow(clippy::manual_saturating_arithmetic)]
                 let min_high_bits = bits.checked_sub(u32::BITS).unwrap_or_default();
                 let max_high_bits = min(bits, u32::BITS);
                 let high_bits = rng.gen_range(min_high_bits..=max_high_bits);
@@ -140,7 +249,9 @@ impl Distribution<u64> for Standard {
                 value
             },
             _ => panic!("bits count out of range"),
-        }
+        };
+        paranoid::weight("Standard", value.into_u128(), bits, select::ones(u64::BITS));
+        value
     }
 }
 
@@ -149,10 +260,11 @@ impl Distribution<u128> for Standard {
     where
         R: Rng + ?Sized,
     {
-        match bits {
+        let value = match bits {
             0 => u128::MIN,
             u128::BITS => u128::MAX,
             bits if (1..u128::BITS).contains(&bits) => {
+                #[allow(clippy::manual_saturating_arithmetic)]
                 let min_high_bits = bits.checked_sub(u64::BITS).unwrap_or_default();
                 let max_high_bits = min(bits, u64::BITS);
                 let high_bits = rng.gen_range(min_high_bits..=max_high_bits);
@@ -164,10 +276,82 @@ impl Distribution<u128> for Standard {
                 value
             },
             _ => panic!("bits count out of range"),
+        };
+        paranoid::weight("Standard", value.into_u128(), bits, select::ones(u128::BITS));
+        value
+    }
+}
+
+/// Generates valid Unicode scalar values whose 21-bit code point has `bits` set bits, uniformly
+/// over all such scalar values.
+///
+/// # Panics
+///
+/// Panics if `bits` is greater than 20, as no scalar value has more set bits.
+impl Distribution<char> for Standard {
+    fn sample<R>(&self, rng: &mut R, bits: u32) -> char
+    where
+        R: Rng + ?Sized,
+    {
+        const WIDTH: u32 = 21;
+        const SURROGATES: RangeInclusive<u128> = 0xD800..=0xDFFF;
+        const END: u128 = char::MAX as u128 + 1;
+
+        assert!(bits <= WIDTH, "bits count out of range");
+        let before = combination::count_below(*SURROGATES.start(), bits);
+        let after = combination::count_below(*SURROGATES.end() + 1, bits);
+        let total = before + combination::count_below(END, bits) - after;
+        assert!(total > 0, "bits count out of range");
+        let mut rank = rng.gen_range(0..total);
+        if rank >= before {
+            // skip the surrogates
+            rank += after - before;
         }
+        let value = combination::unrank(WIDTH, bits, rank);
+        paranoid::weight("Standard", value, bits, select::ones(WIDTH));
+        char::from_u32(value as u32).unwrap()
     }
 }
 
+macro_rules! impl_tuple {
+    ($($t:ident),+) => {
+        /// Generates tuples whose elements have `bits` set bits in total, uniformly over all such
+        /// tuples, i.e. as if the elements were a single value of their total width.
+        ///
+        /// The set bits are split among the elements hypergeometrically, then every element is
+        /// chosen uniformly among the values with its share of set bits.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `bits` is greater than the total width of the elements.
+        impl<$($t),+> Distribution<($($t,)+)> for Standard
+        where
+            $($t: Word,)+
+        {
+            #[allow(unused_assignments)]
+            fn sample<R>(&self, rng: &mut R, bits: u32) -> ($($t,)+)
+            where
+                R: Rng + ?Sized,
+            {
+                let mut population = 0 $(+ u128::from($t::BITS))+;
+                assert!(u128::from(bits) <= population, "bits count out of range");
+                let mut remaining = u128::from(bits);
+                ($({
+                    // split the set bits like drawing bits without replacement
+                    let bits = hypergeometric::sample(rng, population, remaining, $t::BITS);
+                    population -= u128::from($t::BITS);
+                    remaining -= u128::from(bits);
+                    $t::from_u128(select::sample(rng, select::ones($t::BITS), bits))
+                },)+)
+            }
+        }
+    };
+}
+
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+
 /// An automatically-implemented extension trait on [`rand::Rng`].
 ///
 /// # Example:
@@ -205,10 +389,761 @@ pub trait RngBits: Rng {
     {
         Standard.sample(self, bits)
     }
+
+    /// Return a random value with `bits` set bits, handling an out-of-range number of set bits
+    /// according to `policy`.
+    ///
+    /// See [`Standard::sample_with_policy`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::{Policy, RngBits};
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u16 = rng.gen_bits_with_policy(20, Policy::Clamp).unwrap();
+    /// assert_eq!(x, u16::MAX);
+    /// ```
+    fn gen_bits_with_policy<T>(&mut self, bits: u32, policy: Policy) -> Result<T, Error>
+    where
+        T: Word,
+        Standard: Distribution<T>,
+    {
+        Standard.sample_with_policy(self, bits, policy)
+    }
+
+    /// Fill `dest` with random values, where every value has the number of set bits given at the
+    /// same position of `bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dest` and `bits` have different lengths or any number of set bits is greater
+    /// than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let mut frame = [0u64; 4];
+    /// rng.fill_bits_each(&mut frame, &[0, 8, 32, 64]);
+    /// assert_eq!(frame.map(u64::count_ones), [0, 8, 32, 64]);
+    /// ```
+    fn fill_bits_each<T>(&mut self, dest: &mut [T], bits: &[u32])
+    where
+        Standard: Distribution<T>,
+    {
+        assert_eq!(dest.len(), bits.len(), "slice lengths differ");
+        for (value, &bits) in dest.iter_mut().zip(bits) {
+            *value = Standard.sample(self, bits);
+        }
+    }
+
+    /// Fill `dest` with random bytes with `bits` set bits each.
+    ///
+    /// The bytes are the same as generated one by one with [`RngBits::gen_bits`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than 8.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let mut bytes = vec![0u8; 1 << 20];
+    /// rng.fill_bits_bytes(&mut bytes, 3);
+    /// assert!(bytes.iter().all(|byte| byte.count_ones() == 3));
+    /// ```
+    fn fill_bits_bytes(&mut self, dest: &mut [u8], bits: u32) {
+        for byte in dest {
+            *byte = Standard.sample(self, bits);
+        }
+    }
+
+    /// Generate `count` random values with `bits` set bits in chunks, passing every chunk to
+    /// `callback`.
+    ///
+    /// The values are written into `buffer`, whose length is the size of the chunks (the last
+    /// one may be shorter), so nothing is allocated however many values are generated. The
+    /// first error returned by `callback` stops the generation and is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by `callback`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T` or `buffer` is empty while `count` is
+    /// not zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::{self, Write};
+    ///
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let mut output = Vec::new();
+    /// let mut buffer = [0u8; 256];
+    /// rng.gen_bits_chunks(3, 1_000_000, &mut buffer, |chunk| output.write_all(chunk))?;
+    /// assert_eq!(output.len(), 1_000_000);
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    fn gen_bits_chunks<T, F, E>(&mut self, bits: u32, count: usize, buffer: &mut [T], mut callback: F) -> Result<(), E>
+    where
+        Standard: Distribution<T>,
+        F: FnMut(&[T]) -> Result<(), E>,
+    {
+        assert!(!buffer.is_empty() || count == 0, "empty buffer");
+        let mut remaining = count;
+        while remaining > 0 {
+            let len = min(remaining, buffer.len());
+            let chunk = &mut buffer[..len];
+            for value in chunk.iter_mut() {
+                *value = Standard.sample(self, bits);
+            }
+            callback(chunk)?;
+            remaining -= chunk.len();
+        }
+        Ok(())
+    }
+
+    /// Return a pair of random values with `a_bits` and `b_bits` set bits respectively and no set
+    /// bits in common, uniformly over all such pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a_bits + b_bits` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let (read, write): (u32, u32) = rng.gen_disjoint_pair(5, 20);
+    /// assert_eq!(read.count_ones(), 5);
+    /// assert_eq!(write.count_ones(), 20);
+    /// assert_eq!(read & write, 0);
+    /// ```
+    fn gen_disjoint_pair<T>(&mut self, a_bits: u32, b_bits: u32) -> (T, T)
+    where
+        T: Word,
+    {
+        assert!(a_bits.saturating_add(b_bits) <= T::BITS, "bits count out of range");
+        let a = select::sample(self, select::ones(T::BITS), a_bits);
+        let b = select::sample(self, select::ones(T::BITS) & !a, b_bits);
+        paranoid::constraint("gen_disjoint_pair", a & b == 0, || {
+            format!("values {:#b} and {:#b} have common set bits", a, b)
+        });
+        (T::from_u128(a), T::from_u128(b))
+    }
+
+    /// Return a pair of random values with `a_bits` and `b_bits` set bits respectively and
+    /// exactly `common_bits` set bits in common, uniformly over all such pairs.
+    ///
+    /// The union of the values has `a_bits + b_bits - common_bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `common_bits` is greater than `a_bits` or `b_bits`, or the union of the values
+    /// does not fit in the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let (a, b): (u64, u64) = rng.gen_overlapping_pair(20, 30, 12);
+    /// assert_eq!(a.count_ones(), 20);
+    /// assert_eq!(b.count_ones(), 30);
+    /// assert_eq!((a & b).count_ones(), 12);
+    /// assert_eq!((a | b).count_ones(), 38);
+    /// ```
+    fn gen_overlapping_pair<T>(&mut self, a_bits: u32, b_bits: u32, common_bits: u32) -> (T, T)
+    where
+        T: Word,
+    {
+        assert!(
+            common_bits <= a_bits.min(b_bits) && a_bits.saturating_add(b_bits) - common_bits <= T::BITS,
+            "bits count out of range"
+        );
+        let all = select::ones(T::BITS);
+        let common = select::sample(self, all, common_bits);
+        let a = common | select::sample(self, all & !common, a_bits - common_bits);
+        let b = common | select::sample(self, all & !a, b_bits - common_bits);
+        paranoid::weight("gen_overlapping_pair", a, a_bits, all);
+        paranoid::weight("gen_overlapping_pair", b, b_bits, all);
+        paranoid::constraint("gen_overlapping_pair", (a & b).count_ones() == common_bits, || {
+            format!(
+                "values {:#b} and {:#b} do not have {} common set bits",
+                a, b, common_bits
+            )
+        });
+        (T::from_u128(a), T::from_u128(b))
+    }
+
+    /// Return a chain of random values, each one a subset of the next, with the chosen numbers
+    /// of set bits, uniformly over all such chains.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not sorted in nondecreasing order or its last element is greater
+    /// than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let chain: Vec<u8> = rng.gen_chain(&[1, 3, 6]);
+    /// assert_eq!(chain[0] & chain[1], chain[0]);
+    /// assert_eq!(chain[1] & chain[2], chain[1]);
+    /// ```
+    fn gen_chain<T>(&mut self, bits: &[u32]) -> Vec<T>
+    where
+        T: Word,
+    {
+        assert!(bits.windows(2).all(|w| w[0] <= w[1]), "bits counts not sorted");
+        let mut mask = select::ones(T::BITS);
+        let mut chain: Vec<T> = bits
+            .iter()
+            .rev()
+            .map(|&bits| {
+                mask = select::sample(self, mask, bits);
+                T::from_u128(mask)
+            })
+            .collect();
+        chain.reverse();
+        for link in chain.windows(2) {
+            let (lower, upper) = (link[0].into_u128(), link[1].into_u128());
+            paranoid::constraint("gen_chain", lower & !upper == 0, || {
+                format!("value {:#b} is not a subset of {:#b}", lower, upper)
+            });
+        }
+        chain
+    }
+
+    /// Return a random partition of all bit positions of `T` into disjoint values with the
+    /// chosen numbers of set bits, uniformly over all such partitions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sum of `sizes` is not equal to the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let ways: Vec<u16> = rng.gen_partition(&[8, 4, 4]);
+    /// assert_eq!(ways[0] | ways[1] | ways[2], u16::MAX);
+    /// ```
+    fn gen_partition<T>(&mut self, sizes: &[u32]) -> Vec<T>
+    where
+        T: Word,
+    {
+        assert_eq!(sizes.iter().sum::<u32>(), T::BITS, "sizes do not sum to the width");
+        let mut mask = select::ones(T::BITS);
+        let partition = sizes
+            .iter()
+            .map(|&bits| {
+                let part = select::sample(self, mask, bits);
+                mask ^= part;
+                T::from_u128(part)
+            })
+            .collect();
+        paranoid::constraint("gen_partition", mask == 0, || {
+            format!("positions {:#b} left out of the partition", mask)
+        });
+        partition
+    }
+
+    /// Return a random value with `bits` set bits and exactly `zeros` leading zeros, i.e. with
+    /// the highest set bit at a prescribed position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value of `T` has both `bits` set bits and `zeros` leading zeros.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u32 = rng.gen_bits_with_leading_zeros(4, 8);
+    /// assert_eq!(x.count_ones(), 4);
+    /// assert_eq!(x.leading_zeros(), 8);
+    /// ```
+    fn gen_bits_with_leading_zeros<T>(&mut self, bits: u32, zeros: u32) -> T
+    where
+        T: Word,
+    {
+        match (bits, zeros) {
+            (0, zeros) if zeros == T::BITS => T::from_u128(0),
+            (bits, zeros) if bits > 0 && zeros < T::BITS => {
+                let position = T::BITS - zeros - 1;
+                let value = select::sample(self, select::ones(position), bits - 1) | (1 << position);
+                paranoid::weight("gen_bits_with_leading_zeros", value, bits, select::ones(T::BITS));
+                paranoid::constraint(
+                    "gen_bits_with_leading_zeros",
+                    leading_zeros::<T>(value) == zeros,
+                    || format!("value {:#b} does not have {} leading zeros", value, zeros),
+                );
+                T::from_u128(value)
+            },
+            _ => panic!("leading zeros count out of range"),
+        }
+    }
+
+    /// Return a random value with `bits` set bits and at least `zeros` leading zeros, uniformly
+    /// over all such values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits + zeros` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u64 = rng.gen_bits_with_min_leading_zeros(10, 40);
+    /// assert_eq!(x.count_ones(), 10);
+    /// assert!(x.leading_zeros() >= 40);
+    /// ```
+    fn gen_bits_with_min_leading_zeros<T>(&mut self, bits: u32, zeros: u32) -> T
+    where
+        T: Word,
+    {
+        assert!(zeros <= T::BITS, "leading zeros count out of range");
+        let value = select::sample(self, select::ones(T::BITS - zeros), bits);
+        paranoid::constraint(
+            "gen_bits_with_min_leading_zeros",
+            leading_zeros::<T>(value) >= zeros,
+            || format!("value {:#b} has less than {} leading zeros", value, zeros),
+        );
+        T::from_u128(value)
+    }
+
+    /// Return a random value with `bits` set bits and exactly `zeros` trailing zeros, i.e. with
+    /// the lowest set bit at a prescribed position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value of `T` has both `bits` set bits and `zeros` trailing zeros.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u32 = rng.gen_bits_with_trailing_zeros(4, 8);
+    /// assert_eq!(x.count_ones(), 4);
+    /// assert_eq!(x.trailing_zeros(), 8);
+    /// ```
+    fn gen_bits_with_trailing_zeros<T>(&mut self, bits: u32, zeros: u32) -> T
+    where
+        T: Word,
+    {
+        match (bits, zeros) {
+            (0, zeros) if zeros == T::BITS => T::from_u128(0),
+            (bits, zeros) if bits > 0 && zeros < T::BITS => {
+                let mask = select::ones(T::BITS) ^ select::ones(zeros + 1);
+                let value = select::sample(self, mask, bits - 1) | (1 << zeros);
+                paranoid::weight("gen_bits_with_trailing_zeros", value, bits, select::ones(T::BITS));
+                paranoid::constraint("gen_bits_with_trailing_zeros", value.trailing_zeros() == zeros, || {
+                    format!("value {:#b} does not have {} trailing zeros", value, zeros)
+                });
+                T::from_u128(value)
+            },
+            _ => panic!("trailing zeros count out of range"),
+        }
+    }
+
+    /// Return a random value with `bits` set bits and at least `zeros` trailing zeros, i.e.
+    /// aligned to `2^zeros`, uniformly over all such values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits + zeros` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u64 = rng.gen_bits_with_min_trailing_zeros(10, 12);
+    /// assert_eq!(x.count_ones(), 10);
+    /// assert_eq!(x % 4096, 0);
+    /// ```
+    fn gen_bits_with_min_trailing_zeros<T>(&mut self, bits: u32, zeros: u32) -> T
+    where
+        T: Word,
+    {
+        assert!(zeros <= T::BITS, "trailing zeros count out of range");
+        let mask = select::ones(T::BITS) ^ select::ones(zeros);
+        let value = select::sample(self, mask, bits);
+        paranoid::constraint(
+            "gen_bits_with_min_trailing_zeros",
+            value.trailing_zeros() >= zeros,
+            || format!("value {:#b} has less than {} trailing zeros", value, zeros),
+        );
+        T::from_u128(value)
+    }
+
+    /// Return a random value with `bits` set bits which stays unchanged under `symmetry`,
+    /// uniformly over all such values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no such value of `T` exists, e.g. when `bits` is odd and no bit position is
+    /// fixed by the reordering.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::{RngBits, Symmetry};
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u32 = rng.gen_bits_symmetric(10, Symmetry::BitReversal);
+    /// assert_eq!(x.count_ones(), 10);
+    /// assert_eq!(x.reverse_bits(), x);
+    /// ```
+    fn gen_bits_symmetric<T>(&mut self, bits: u32, symmetry: Symmetry) -> T
+    where
+        T: Word,
+    {
+        T::from_u128(symmetry::sample(self, T::BITS, bits, symmetry))
+    }
+
+    /// Return a random value with `bits` set bits within `range`, uniformly over all such
+    /// values.
+    ///
+    /// The value is chosen by its rank among all values with `bits` set bits, so the cost does
+    /// not depend on how many values within `range` are rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if no value within `range` has `bits` set bits.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u32 = rng.gen_bits_in_value_range(5, 1000..=2000).unwrap();
+    /// assert_eq!(x.count_ones(), 5);
+    /// assert!((1000..=2000).contains(&x));
+    /// ```
+    fn gen_bits_in_value_range<T>(&mut self, bits: u32, range: RangeInclusive<T>) -> Result<T, Error>
+    where
+        T: Word,
+    {
+        let (start, end) = (range.start().into_u128(), range.end().into_u128());
+        let low = combination::count_below(start, bits);
+        let high = combination::count_below(end, bits) + u128::from(end.count_ones() == bits);
+        if low >= high {
+            return Err(Error::Infeasible);
+        }
+        let rank = self.gen_range(low..high);
+        let value = combination::unrank(T::BITS, bits, rank);
+        paranoid::weight("gen_bits_in_value_range", value, bits, select::ones(T::BITS));
+        paranoid::constraint("gen_bits_in_value_range", (start..=end).contains(&value), || {
+            format!("value {} outside the range {}..={}", value, start, end)
+        });
+        Ok(T::from_u128(value))
+    }
+
+    /// Return a random value with `bits` set bits whose highest `len` bits are equal to the
+    /// lowest `len` bits of `prefix`, uniformly over all such values.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if no value with the prefix has `bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the width of `T` or `prefix` does not fit in `len` bits.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u32 = rng.gen_bits_with_prefix(12, 0b1011, 4).unwrap();
+    /// assert_eq!(x.count_ones(), 12);
+    /// assert_eq!(x >> 28, 0b1011);
+    /// ```
+    fn gen_bits_with_prefix<T>(&mut self, bits: u32, prefix: T, len: u32) -> Result<T, Error>
+    where
+        T: Word,
+    {
+        assert!(len <= T::BITS, "prefix length out of range");
+        let prefix = prefix.into_u128();
+        assert_eq!(prefix & !select::ones(len), 0, "prefix out of range");
+
+        let width = T::BITS - len;
+        let bits = bits.checked_sub(prefix.count_ones()).ok_or(Error::Infeasible)?;
+        if bits > width {
+            return Err(Error::Infeasible);
+        }
+        let value = select::sample(self, select::ones(width), bits);
+        paranoid::constraint(
+            "gen_bits_with_prefix",
+            value.checked_shr(width).unwrap_or_default() == 0,
+            || format!("value {:#b} overwrites the prefix", value),
+        );
+        let prefix = prefix.checked_shl(width).unwrap_or_default();
+        Ok(T::from_u128(prefix | value))
+    }
+
+    /// Return a batch of `count` random values with `bits` set bits each, where every bit
+    /// position is set equally often, i.e. either `floor(count * bits / T::BITS)` or
+    /// `ceil(count * bits / T::BITS)` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let batch: Vec<u8> = rng.gen_balanced_batch(3, 16);
+    /// for position in 0..8 {
+    ///     assert_eq!(batch.iter().filter(|&&n| n >> position & 1 == 1).count(), 6);
+    /// }
+    /// ```
+    fn gen_balanced_batch<T>(&mut self, bits: u32, count: usize) -> Vec<T>
+    where
+        T: Word,
+    {
+        balanced::sample(self, T::BITS, bits, count)
+            .into_iter()
+            .map(T::from_u128)
+            .collect()
+    }
+
+    /// Return a random value with `bits` set bits together with its bitwise complement, which
+    /// has `T::BITS - bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let (x, y): (u64, u64) = rng.gen_bits_antithetic(10);
+    /// assert_eq!(x.count_ones(), 10);
+    /// assert_eq!(y, !x);
+    /// ```
+    fn gen_bits_antithetic<T>(&mut self, bits: u32) -> (T, T)
+    where
+        T: Word,
+        Standard: Distribution<T>,
+    {
+        let value = Standard.sample(self, bits);
+        (value, antithetic::complement(value))
+    }
+
+    /// Return a random value with `bits` set bits together with its index among all such values
+    /// in ascending order.
+    ///
+    /// See [`WeightClass::choose_with_rank`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::{RngBits, WeightClass};
+    ///
+    /// let mut rng = thread_rng();
+    /// let (x, rank): (u16, u128) = rng.gen_bits_with_rank(3);
+    /// assert_eq!(WeightClass::<u16>::new(3).get(rank), Some(x));
+    /// ```
+    fn gen_bits_with_rank<T>(&mut self, bits: u32) -> (T, u128)
+    where
+        T: Word,
+    {
+        WeightClass::new(bits).choose_with_rank(self)
+    }
+
+    /// Return a random value with `bits` set bits accepted by `predicate`, retrying at most
+    /// `attempts` times.
+    ///
+    /// Every attempt generates a new uniformly chosen value, so the result is uniform over the
+    /// accepted values.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AttemptsExhausted`] if no value was accepted within `attempts` attempts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::thread_rng;
+    /// use rand_bits::RngBits;
+    ///
+    /// let mut rng = thread_rng();
+    /// let x: u32 = rng.gen_bits_where(8, 1000, |n: u32| n % 3 == 0).unwrap();
+    /// assert_eq!(x.count_ones(), 8);
+    /// assert_eq!(x % 3, 0);
+    /// ```
+    fn gen_bits_where<T, P>(&mut self, bits: u32, attempts: usize, mut predicate: P) -> Result<T, Error>
+    where
+        T: Word,
+        P: FnMut(T) -> bool,
+    {
+        assert!(bits <= T::BITS, "bits count out of range");
+        for _ in 0..attempts {
+            let value = T::from_u128(select::sample(self, select::ones(T::BITS), bits));
+            if predicate(value) {
+                return Ok(value);
+            }
+        }
+        Err(Error::AttemptsExhausted)
+    }
 }
 
 impl<R> RngBits for R where R: Rng {}
 
+/// Return `value` changed to have `bits` set bits by flipping as few bits as possible.
+///
+/// If `value` has fewer set bits, uniformly chosen unset bits are set, otherwise uniformly chosen
+/// set bits are unset, so the result differs from `value` in exactly
+/// `|value.count_ones() - bits|` bits and the rest of its pattern is preserved.
+///
+/// # Panics
+///
+/// Panics if `bits` is greater than the width of `T`.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::thread_rng;
+/// use rand_bits::morph_weight;
+///
+/// let x: u32 = 0x0000_FFFF;
+/// let y = morph_weight(x, 20, &mut thread_rng());
+/// assert_eq!(y.count_ones(), 20);
+/// assert_eq!(y & x, x);
+/// ```
+pub fn morph_weight<T, R>(value: T, bits: u32, rng: &mut R) -> T
+where
+    T: Word,
+    R: Rng + ?Sized,
+{
+    assert!(bits <= T::BITS, "bits count out of range");
+    let current = value.count_ones();
+    let value = value.into_u128();
+    let flipped = if bits >= current {
+        select::sample(rng, select::ones(T::BITS) & !value, bits - current)
+    } else {
+        select::sample(rng, value, current - bits)
+    };
+    let morphed = value ^ flipped;
+    paranoid::weight("morph_weight", morphed, bits, select::ones(T::BITS));
+    paranoid::constraint("morph_weight", flipped.count_ones() == current.abs_diff(bits), || {
+        format!(
+            "{:#b} flipped {} bits instead of {}",
+            value,
+            flipped.count_ones(),
+            current.abs_diff(bits)
+        )
+    });
+    T::from_u128(morphed)
+}
+
+/// Return a value with `bits` set bits derived deterministically from `x`.
+///
+/// The input is mixed into a 128-bit hash which is reduced to an index among all values with
+/// `bits` set bits by taking the high half of its product with their number. Every index is hit
+/// by either `floor(2^128 / n)` or `ceil(2^128 / n)` of the `2^128` hashes of `n` values, so
+/// for uniform hashes the probability of every value is within a relative `n / 2^128` of `1 / n`:
+/// below `2^-67` for every class of `u64`, but up to `2^-3` for the largest classes of `u128`.
+/// As the input has 64 bits, classes of more than `2^64` values are never fully covered. The
+/// mapping is the same on every platform, which makes it suitable for constant-weight encoding
+/// of hashes and consistent per-key masks.
+///
+/// # Panics
+///
+/// Panics if `bits` is greater than the width of `T`.
+///
+/// # Example
+///
+/// ```rust
+/// use rand_bits::to_fixed_weight;
+///
+/// let mask: u64 = to_fixed_weight(0xDEAD_BEEF, 16);
+/// assert_eq!(mask.count_ones(), 16);
+/// assert_eq!(to_fixed_weight::<u64>(0xDEAD_BEEF, 16), mask);
+/// ```
+pub fn to_fixed_weight<T>(x: u64, bits: u32) -> T
+where
+    T: Word,
+{
+    let class = WeightClass::<T>::new(bits);
+    let mut state = x;
+    let hash = (u128::from(hash::splitmix64(&mut state)) << 64) | u128::from(hash::splitmix64(&mut state));
+    let value = class.get(hash::mul_high(hash, class.len())).unwrap();
+    paranoid::weight("to_fixed_weight", value.into_u128(), bits, select::ones(T::BITS));
+    value
+}
+
+/// Return the number of leading zeros of `value` as a value of `T`.
+fn leading_zeros<T>(value: u128) -> u32
+where
+    T: Word,
+{
+    value.leading_zeros() - (u128::BITS - T::BITS)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,4 +1192,335 @@ mod tests {
             assert_eq!(n.count_ones(), i);
         }
     }
+
+    #[test]
+    fn disjoint_pair() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u16::BITS {
+            for j in 0..=(u16::BITS - i) {
+                let (a, b): (u16, u16) = rng.gen_disjoint_pair(i, j);
+                assert_eq!(a.count_ones(), i);
+                assert_eq!(b.count_ones(), j);
+                assert_eq!(a & b, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_pair() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            for j in 0..=u8::BITS {
+                for k in 0..=i.min(j) {
+                    if i + j - k > u8::BITS {
+                        continue;
+                    }
+                    let (a, b): (u8, u8) = rng.gen_overlapping_pair(i, j, k);
+                    assert_eq!((a.count_ones(), b.count_ones(), (a & b).count_ones()), (i, j, k));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chain() {
+        let mut rng = rand::thread_rng();
+        let bits = [0, 1, 1, 5, 30, 31, 32];
+        let chain: Vec<u32> = rng.gen_chain(&bits);
+        for (n, i) in chain.iter().zip(bits) {
+            assert_eq!(n.count_ones(), i);
+        }
+        for w in chain.windows(2) {
+            assert_eq!(w[0] & w[1], w[0]);
+        }
+    }
+
+    #[test]
+    fn partition() {
+        let mut rng = rand::thread_rng();
+        let sizes = [0, 3, 1, 20, 0, 40];
+        let parts: Vec<u64> = rng.gen_partition(&sizes);
+        for (n, i) in parts.iter().zip(sizes) {
+            assert_eq!(n.count_ones(), i);
+        }
+        assert_eq!(parts.iter().fold(0, |acc, n| acc | n), u64::MAX);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        let mut rng = rand::thread_rng();
+        let n: u16 = rng.gen_bits_with_leading_zeros(0, u16::BITS);
+        assert_eq!(n, 0);
+        for zeros in 0..u16::BITS {
+            for i in 1..=(u16::BITS - zeros) {
+                let n: u16 = rng.gen_bits_with_leading_zeros(i, zeros);
+                assert_eq!(n.count_ones(), i);
+                assert_eq!(n.leading_zeros(), zeros);
+            }
+        }
+        for zeros in 0..=u16::BITS {
+            for i in 0..=(u16::BITS - zeros) {
+                let n: u16 = rng.gen_bits_with_min_leading_zeros(i, zeros);
+                assert_eq!(n.count_ones(), i);
+                assert!(n.leading_zeros() >= zeros);
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_zeros() {
+        let mut rng = rand::thread_rng();
+        let n: u16 = rng.gen_bits_with_trailing_zeros(0, u16::BITS);
+        assert_eq!(n, 0);
+        for zeros in 0..u16::BITS {
+            for i in 1..=(u16::BITS - zeros) {
+                let n: u16 = rng.gen_bits_with_trailing_zeros(i, zeros);
+                assert_eq!(n.count_ones(), i);
+                assert_eq!(n.trailing_zeros(), zeros);
+            }
+        }
+        for zeros in 0..=u16::BITS {
+            for i in 0..=(u16::BITS - zeros) {
+                let n: u16 = rng.gen_bits_with_min_trailing_zeros(i, zeros);
+                assert_eq!(n.count_ones(), i);
+                assert!(n.trailing_zeros() >= zeros);
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric() {
+        let mut rng = rand::thread_rng();
+        for i in (0..=u128::BITS).step_by(2) {
+            let n: u128 = rng.gen_bits_symmetric(i, Symmetry::BitReversal);
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(n.reverse_bits(), n);
+            let n: u128 = rng.gen_bits_symmetric(i, Symmetry::ByteReversal);
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(n.swap_bytes(), n);
+        }
+    }
+
+    #[test]
+    fn value_range() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u16::BITS {
+            for (start, end) in [(0, u16::MAX), (1000, 1100), (0x00FF, 0x0100), (0x8000, 0x8000)] {
+                let expected = (start..=end).any(|n: u16| n.count_ones() == i);
+                match rng.gen_bits_in_value_range::<u16>(i, start..=end) {
+                    Ok(n) => {
+                        assert!(expected);
+                        assert_eq!(n.count_ones(), i);
+                        assert!((start..=end).contains(&n));
+                    },
+                    Err(error) => {
+                        assert_eq!(error, Error::Infeasible);
+                        assert!(!expected);
+                    },
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn prefix() {
+        let mut rng = rand::thread_rng();
+        for len in 0..=u8::BITS {
+            for prefix in 0..(1u16 << len) {
+                let prefix = prefix as u8;
+                for i in 0..=u8::BITS {
+                    let width = u8::BITS - len;
+                    match rng.gen_bits_with_prefix::<u8>(i, prefix, len) {
+                        Ok(n) => {
+                            assert_eq!(n.count_ones(), i);
+                            assert_eq!(n.checked_shr(width).unwrap_or_default(), prefix);
+                        },
+                        Err(_) => assert!(i < prefix.count_ones() || i > prefix.count_ones() + width),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn antithetic() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let (x, y): (u8, u8) = rng.gen_bits_antithetic(i);
+            assert_eq!(x.count_ones(), i);
+            assert_eq!(y, !x);
+        }
+    }
+
+    #[test]
+    fn with_rank() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u64::BITS {
+            let (n, rank): (u64, u128) = rng.gen_bits_with_rank(i);
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(WeightClass::<u64>::new(i).get(rank), Some(n));
+        }
+    }
+
+    #[test]
+    fn predicate() {
+        let mut rng = rand::thread_rng();
+        for i in 1..=u16::BITS {
+            let n: u16 = rng.gen_bits_where(i, 1000, |n: u16| n & 1 == 1).unwrap();
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(n & 1, 1);
+        }
+        let result = rng.gen_bits_where(4, 100, |n: u16| n.count_ones() != 4);
+        assert_eq!(result, Err(Error::AttemptsExhausted));
+        assert_eq!(rng.gen_bits_where(4, 0, |_: u16| true), Err(Error::AttemptsExhausted));
+        // each of the 120 values about 1000 times, across the bytes too
+        let mut counts = vec![0u32; 1 << u16::BITS];
+        for _ in 0..120_000 {
+            let n: u16 = rng.gen_bits_where(2, 1, |_| true).unwrap();
+            counts[usize::from(n)] += 1;
+        }
+        assert_eq!(counts.iter().filter(|&&count| count > 0).count(), 120);
+        assert!(counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .all(|count| (800..1200).contains(count)));
+    }
+
+    #[test]
+    fn policy() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=2 * u32::BITS {
+            let n: Result<u32, Error> = rng.gen_bits_with_policy(i, Policy::Clamp);
+            assert_eq!(n.unwrap().count_ones(), i.min(u32::BITS));
+            let n: Result<u32, Error> = rng.gen_bits_with_policy(i, Policy::Error);
+            match n {
+                Ok(n) => assert_eq!(n.count_ones(), i),
+                Err(error) => {
+                    assert_eq!(error, Error::BitsOutOfRange);
+                    assert!(i > u32::BITS);
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn fill_each() {
+        let mut rng = rand::thread_rng();
+        let bits: Vec<u32> = (0..=u32::BITS).rev().collect();
+        let mut values = vec![0u32; bits.len()];
+        rng.fill_bits_each(&mut values, &bits);
+        for (n, i) in values.into_iter().zip(bits) {
+            assert_eq!(n.count_ones(), i);
+        }
+    }
+
+    #[test]
+    fn fill_bytes() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let mut bytes = [0u8; 1000];
+            rng.fill_bits_bytes(&mut bytes, i);
+            assert!(bytes.iter().all(|byte| byte.count_ones() == i));
+        }
+        // the same bytes as generated one by one
+        let mut rng = stream::ChaCha12Rng::seed_from_u64(7);
+        let mut copy = rng.clone();
+        let mut bytes = [0u8; 100];
+        rng.fill_bits_bytes(&mut bytes, 3);
+        assert!(bytes.iter().all(|&byte| byte == copy.gen_bits::<u8>(3)));
+    }
+
+    #[test]
+    fn chunks() {
+        let mut rng = rand::thread_rng();
+        let mut buffer = [0u16; 7];
+        let mut lengths = Vec::new();
+        let result: Result<(), ()> = rng.gen_bits_chunks(9, 30, &mut buffer, |chunk| {
+            assert!(chunk.iter().all(|n| n.count_ones() == 9));
+            lengths.push(chunk.len());
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(lengths, [7, 7, 7, 7, 2]);
+
+        let mut calls = 0;
+        let result = rng.gen_bits_chunks(9, 30, &mut buffer, |_| {
+            calls += 1;
+            Err(calls)
+        });
+        assert_eq!(result, Err(1));
+    }
+
+    #[test]
+    fn fixed_weight() {
+        for i in 0..=u128::BITS {
+            let n: u128 = to_fixed_weight(u64::from(i), i);
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(to_fixed_weight::<u128>(u64::from(i), i), n);
+        }
+        // stable across platforms and releases
+        assert_eq!(to_fixed_weight::<u8>(0, 4), 0xD1);
+        // each of the 70 values about 1000 times
+        let mut counts = [0u32; 256];
+        for x in 0..70_000 {
+            counts[usize::from(to_fixed_weight::<u8>(x, 4))] += 1;
+        }
+        assert_eq!(counts.iter().filter(|&&count| count > 0).count(), 70);
+        assert!(counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .all(|count| (850..1150).contains(count)));
+    }
+
+    #[test]
+    fn char() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=20 {
+            for _ in 0..100 {
+                let c: char = rng.gen_bits(i);
+                assert_eq!(u32::from(c).count_ones(), i);
+            }
+        }
+        let count = (0..=u32::from(char::MAX))
+            .filter_map(char::from_u32)
+            .filter(|&c| u32::from(c).count_ones() == 20)
+            .count();
+        assert_eq!(count, 1);
+        assert_eq!(rng.gen_bits::<char>(20), '\u{FFFFF}');
+        assert!((0..1000).all(|_| !(0xD800..=0xDFFF).contains(&u32::from(rng.gen_bits::<char>(11)))));
+    }
+
+    #[test]
+    fn tuple() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=(8 + 32 + 64) {
+            let (a, b, c): (u8, u32, u64) = rng.gen_bits(i);
+            assert_eq!(a.count_ones() + b.count_ones() + c.count_ones(), i);
+        }
+        // the byte holds on average 8 of the 24 bits
+        let total: u32 = (0..10_000).map(|_| rng.gen_bits::<(u8, u16)>(6).0.count_ones()).sum();
+        assert!((19_000..21_000).contains(&total));
+        // each of the C(24, 2) = 276 tuples about 300 times, whichever elements hold the bits
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..(276 * 300) {
+            *counts.entry(rng.gen_bits::<(u16, u8)>(2)).or_insert(0u32) += 1;
+        }
+        assert_eq!(counts.len(), 276);
+        assert!(counts.values().all(|count| (200..400).contains(count)));
+    }
+
+    #[test]
+    fn morph() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u64::BITS {
+            for j in 0..=u64::BITS {
+                let x: u64 = rng.gen_bits(i);
+                let y = morph_weight(x, j, &mut rng);
+                assert_eq!(y.count_ones(), j);
+                assert_eq!((x ^ y).count_ones(), i.abs_diff(j));
+            }
+        }
+    }
 }
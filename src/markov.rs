@@ -0,0 +1,118 @@
+//! Correlated sequences of values with a fixed number of set bits (ones).
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::markov::SwapChain;
+//!
+//! let masks: Vec<u64> = SwapChain::new(thread_rng(), 16, 2).take(100).collect();
+//! for w in masks.windows(2) {
+//!     assert_eq!(w[0].count_ones(), 16);
+//!     assert!((w[0] ^ w[1]).count_ones() <= 4);
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::{paranoid, select, Word};
+
+/// An infinite Markov chain over values with a fixed number of set bits, where every step
+/// swaps a chosen number of randomly selected set and unset bits.
+///
+/// The first value is uniformly chosen (or given) and every following one differs from its
+/// predecessor by at most `swaps` swaps, so the number of swaps controls the temporal
+/// correlation. The uniform distribution is the stationary distribution of the chain.
+#[derive(Clone, Debug)]
+pub struct SwapChain<R, T> {
+    rng: R,
+    value: u128,
+    swaps: u32,
+    started: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<R, T> SwapChain<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    /// Create a new chain of values with `bits` set bits, performing `swaps` swaps per step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    pub fn new(mut rng: R, bits: u32, swaps: u32) -> Self {
+        assert!(bits <= T::BITS, "bits count out of range");
+        let value = select::sample(&mut rng, select::ones(T::BITS), bits);
+        Self {
+            rng,
+            value,
+            swaps,
+            started: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new chain starting from `value`, performing `swaps` swaps per step.
+    pub fn starting_at(rng: R, value: T, swaps: u32) -> Self {
+        Self {
+            rng,
+            value: value.into_u128(),
+            swaps,
+            started: false,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<R, T> Iterator for SwapChain<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mask = select::ones(T::BITS);
+        if self.started && self.value != 0 && self.value != mask {
+            let bits = self.value.count_ones();
+            for _ in 0..self.swaps {
+                let one = select::sample(&mut self.rng, self.value, 1);
+                let zero = select::sample(&mut self.rng, !self.value & mask, 1);
+                self.value ^= one | zero;
+            }
+            paranoid::weight("SwapChain", self.value, bits, mask);
+        }
+        self.started = true;
+        Some(T::from_u128(self.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain() {
+        let rng = rand::thread_rng();
+        for i in 0..=u32::BITS {
+            for swaps in [0, 1, 3] {
+                let values: Vec<u32> = SwapChain::new(rng.clone(), i, swaps).take(32).collect();
+                for w in values.windows(2) {
+                    assert_eq!(w[1].count_ones(), i);
+                    assert!((w[0] ^ w[1]).count_ones() <= 2 * swaps);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn starting_at() {
+        let mut chain = SwapChain::starting_at(rand::thread_rng(), 0x0Fu8, 1);
+        assert_eq!(chain.next(), Some(0x0F));
+        assert_eq!((chain.next().unwrap() ^ 0x0F).count_ones(), 2);
+    }
+}
@@ -0,0 +1,150 @@
+//! Mixtures of several distributions chosen per sample.
+
+use std::fmt::{self, Debug, Formatter};
+
+use rand::{Rng, RngCore};
+
+/// An object-safe counterpart of [`rand::distributions::Distribution`].
+trait Component<T> {
+    fn sample_dyn(&self, rng: &mut dyn RngCore) -> T;
+}
+
+impl<D, T> Component<T> for D
+where
+    D: rand::distributions::Distribution<T>,
+{
+    fn sample_dyn(&self, rng: &mut dyn RngCore) -> T {
+        self.sample(rng)
+    }
+}
+
+/// A distribution choosing one of several component distributions for every sample, with
+/// probabilities proportional to their weights.
+///
+/// Components may be of different types as long as they generate the same type of values, e.g.
+/// a [`WeightClass`](crate::WeightClass) with a fixed number of set bits mixed with a
+/// [`WeightSpectrum`](crate::WeightSpectrum).
+///
+/// # Example
+///
+/// ```rust
+/// use rand::{thread_rng, Rng};
+/// use rand_bits::{Mixture, WeightClass, WeightSpectrum};
+///
+/// let mixture = Mixture::new()
+///     .with(3.0, WeightClass::<u32>::new(1))
+///     .with(1.0, WeightSpectrum::new([0, 0, 1, 1]).unwrap());
+/// let x: u32 = thread_rng().sample(&mixture);
+/// assert!((1..=3).contains(&x.count_ones()));
+/// ```
+pub struct Mixture<'a, T> {
+    components: Vec<Box<dyn Component<T> + 'a>>,
+    cumulative: Vec<f64>,
+}
+
+impl<'a, T> Mixture<'a, T> {
+    /// Create a new mixture without components.
+    pub fn new() -> Self {
+        Self {
+            components: Vec::new(),
+            cumulative: Vec::new(),
+        }
+    }
+
+    /// Add a component chosen with probability proportional to `weight`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is negative or not finite.
+    pub fn with<D>(mut self, weight: f64, component: D) -> Self
+    where
+        D: rand::distributions::Distribution<T> + 'a,
+    {
+        assert!(weight.is_finite() && weight >= 0.0, "invalid weight");
+        let total = self.total() + weight;
+        self.components.push(Box::new(component));
+        self.cumulative.push(total);
+        self
+    }
+
+    /// Return the number of components.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Return `true` if the mixture has no components.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    fn total(&self) -> f64 {
+        self.cumulative.last().copied().unwrap_or_default()
+    }
+}
+
+impl<T> Default for Mixture<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for Mixture<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mixture").field("cumulative", &self.cumulative).finish()
+    }
+}
+
+impl<T> rand::distributions::Distribution<T> for Mixture<'_, T> {
+    /// Generate a random value of `T` with a randomly chosen component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mixture has no component with a positive weight.
+    fn sample<R>(&self, rng: &mut R) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        let total = self.total();
+        assert!(total > 0.0, "empty mixture");
+        let point = rng.gen::<f64>() * total;
+        let index = self.cumulative.partition_point(|&bound| bound <= point);
+        // rounding may push the point onto the total, fall back to the last positive weight
+        let index = match index {
+            index if index < self.len() => index,
+            _ => self.cumulative.partition_point(|&bound| bound < total),
+        };
+        let mut rng = rng;
+        self.components[index].sample_dyn(&mut rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WeightClass;
+
+    #[test]
+    fn proportions() {
+        let mut rng = rand::thread_rng();
+        let mixture = Mixture::new()
+            .with(1.0, WeightClass::<u16>::new(2))
+            .with(0.0, WeightClass::<u16>::new(5))
+            .with(3.0, WeightClass::<u16>::new(9));
+        assert_eq!(mixture.len(), 3);
+        let mut counts = [0u32; 17];
+        for _ in 0..10_000 {
+            let n: u16 = rng.sample(&mixture);
+            counts[n.count_ones() as usize] += 1;
+        }
+        assert_eq!(counts[5], 0);
+        assert_eq!(counts[2] + counts[9], 10_000);
+        assert!((2_000..3_000).contains(&counts[2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty mixture")]
+    fn empty() {
+        let mixture = Mixture::<u8>::new().with(0.0, WeightClass::new(1));
+        let _: u8 = rand::thread_rng().sample(&mixture);
+    }
+}
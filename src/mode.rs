@@ -0,0 +1,119 @@
+//! Unix file mode (`mode_t`) words with a fixed number of permission bits set.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::{thread_rng, Rng};
+//! use rand_bits::mode::{Mode, S_IFDIR, S_IFMT};
+//!
+//! let mode = thread_rng().sample(Mode::new(5).file_type(S_IFDIR).without_special());
+//! assert_eq!(mode & S_IFMT, S_IFDIR);
+//! assert_eq!((mode & 0o777).count_ones(), 5);
+//! ```
+
+use rand::Rng;
+
+use crate::select;
+
+/// The mask of the file type bits.
+pub const S_IFMT: u32 = 0o170000;
+/// The socket file type.
+pub const S_IFSOCK: u32 = 0o140000;
+/// The symbolic link file type.
+pub const S_IFLNK: u32 = 0o120000;
+/// The regular file type.
+pub const S_IFREG: u32 = 0o100000;
+/// The block device file type.
+pub const S_IFBLK: u32 = 0o060000;
+/// The directory file type.
+pub const S_IFDIR: u32 = 0o040000;
+/// The character device file type.
+pub const S_IFCHR: u32 = 0o020000;
+/// The FIFO file type.
+pub const S_IFIFO: u32 = 0o010000;
+
+/// The set-user-ID bit.
+pub const S_ISUID: u32 = 0o4000;
+/// The set-group-ID bit.
+pub const S_ISGID: u32 = 0o2000;
+/// The sticky bit.
+pub const S_ISVTX: u32 = 0o1000;
+
+/// The mask of the read, write and execute bits for the owner, group and others.
+pub const ACCESSPERMS: u32 = 0o777;
+/// The mask of all permission bits.
+pub const ALLPERMS: u32 = 0o7777;
+
+/// A distribution of mode words with a fixed number of permission bits set, uniform over all
+/// words satisfying the restrictions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Mode {
+    bits: u32,
+    file_type: u32,
+    allowed: u32,
+}
+
+impl Mode {
+    /// Create a new distribution of mode words with `bits` permission bits set, including the
+    /// set-user-ID, set-group-ID and sticky bits, and with no file type.
+    pub fn new(bits: u32) -> Self {
+        Self {
+            bits,
+            file_type: 0,
+            allowed: ALLPERMS,
+        }
+    }
+
+    /// Pin the file type bits to `file_type`, e.g. [`S_IFREG`].
+    ///
+    /// Bits outside of [`S_IFMT`] are ignored.
+    pub fn file_type(mut self, file_type: u32) -> Self {
+        self.file_type = file_type & S_IFMT;
+        self
+    }
+
+    /// Exclude the set-user-ID, set-group-ID and sticky bits.
+    pub fn without_special(mut self) -> Self {
+        self.allowed &= ACCESSPERMS;
+        self
+    }
+}
+
+impl rand::distributions::Distribution<u32> for Mode {
+    /// Generate a random mode word.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the number of allowed permission bits.
+    fn sample<R>(&self, rng: &mut R) -> u32
+    where
+        R: Rng + ?Sized,
+    {
+        select::sample(rng, u128::from(self.allowed), self.bits) as u32 | self.file_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=12 {
+            let mode = rng.sample(Mode::new(i).file_type(S_IFREG));
+            assert_eq!(mode & S_IFMT, S_IFREG);
+            assert_eq!((mode & !S_IFMT).count_ones(), i);
+        }
+    }
+
+    #[test]
+    fn without_special() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=9 {
+            let mode = rng.sample(Mode::new(i).without_special());
+            assert_eq!(mode & !ACCESSPERMS, 0);
+            assert_eq!(mode.count_ones(), i);
+        }
+    }
+}
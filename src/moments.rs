@@ -0,0 +1,156 @@
+//! Exact moments of the numeric value of uniformly chosen values with a fixed number of set bits
+//! (ones).
+//!
+//! Every bit of a `width`-bit value with `bits` set bits is set with probability
+//! `bits / width`, and any two distinct bits are slightly negatively correlated, since setting
+//! one of them leaves fewer set bits for the other.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand_bits::moments::Moments;
+//!
+//! let moments = Moments::new(8, 4);
+//! assert_eq!(moments.mean(), 127.5);
+//! assert_eq!(moments.bit_mean(), 0.5);
+//! assert!(moments.bit_covariance(0, 1) < 0.0);
+//! ```
+
+/// The exact moments of the value of a `width`-bit variable uniformly chosen from all values
+/// with `bits` set bits.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Moments {
+    width: u32,
+    bits: u32,
+}
+
+impl Moments {
+    /// Create the moments of `width`-bit values with `bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is greater than 128 or `bits` is greater than `width`.
+    pub fn new(width: u32, bits: u32) -> Self {
+        assert!(width <= u128::BITS, "width out of range");
+        assert!(bits <= width, "bits count out of range");
+        Self { width, bits }
+    }
+
+    /// Return the width of values.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Return the number of set bits of values.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Return the mean of the value.
+    pub fn mean(&self) -> f64 {
+        self.bit_mean() * self.sum()
+    }
+
+    /// Return the variance of the value.
+    pub fn variance(&self) -> f64 {
+        // the sum of the covariances of all pairs of bits weighted by their place values
+        let squares = self.sum_of_squares();
+        let variance = self.bit_variance() * squares + self.pair_covariance() * (self.sum().powi(2) - squares);
+        variance.max(0.0)
+    }
+
+    /// Return the standard deviation of the value.
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Return the probability of every single bit being set, i.e. the mean of every bit.
+    pub fn bit_mean(&self) -> f64 {
+        match self.width {
+            0 => 0.0,
+            width => f64::from(self.bits) / f64::from(width),
+        }
+    }
+
+    /// Return the variance of every single bit.
+    pub fn bit_variance(&self) -> f64 {
+        let mean = self.bit_mean();
+        mean * (1.0 - mean)
+    }
+
+    /// Return the covariance of the bits at positions `i` and `j`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position is out of the width.
+    pub fn bit_covariance(&self, i: u32, j: u32) -> f64 {
+        assert!(i < self.width && j < self.width, "position out of range");
+        match i == j {
+            true => self.bit_variance(),
+            false => self.pair_covariance(),
+        }
+    }
+
+    /// Return the correlation of the bits at distinct positions, or zero if the bits are
+    /// constant.
+    pub fn bit_correlation(&self) -> f64 {
+        match self.bit_variance() {
+            variance if variance > 0.0 => self.pair_covariance() / variance,
+            _ => 0.0,
+        }
+    }
+
+    /// Return the covariance of two bits at distinct positions.
+    fn pair_covariance(&self) -> f64 {
+        if self.width < 2 {
+            return 0.0;
+        }
+        let (width, bits) = (f64::from(self.width), f64::from(self.bits));
+        -bits * (width - bits) / (width * width * (width - 1.0))
+    }
+
+    /// Return the sum of the place values, `2^width - 1`.
+    fn sum(&self) -> f64 {
+        2f64.powi(self.width as i32) - 1.0
+    }
+
+    /// Return the sum of the squared place values, `(4^width - 1) / 3`.
+    fn sum_of_squares(&self) -> f64 {
+        (4f64.powi(self.width as i32) - 1.0) / 3.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WeightClass;
+
+    #[test]
+    fn exhaustive() {
+        for i in 0..=u8::BITS {
+            let values: Vec<f64> = WeightClass::<u8>::new(i).into_iter().map(f64::from).collect();
+            let len = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / len;
+            let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / len;
+            let moments = Moments::new(u8::BITS, i);
+            assert!((moments.mean() - mean).abs() < 1e-9);
+            assert!((moments.variance() - variance).abs() < 1e-6);
+
+            let both = WeightClass::<u8>::new(i)
+                .into_iter()
+                .filter(|value| value & 0b11 == 0b11)
+                .count();
+            let covariance = both as f64 / len - moments.bit_mean().powi(2);
+            assert!((moments.bit_covariance(0, 1) - covariance).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn wide() {
+        let moments = Moments::new(u128::BITS, 64);
+        assert_eq!(moments.mean(), (u128::MAX / 2) as f64);
+        assert!(moments.std_dev() > 0.0);
+        assert_eq!(Moments::new(u128::BITS, 0).variance(), 0.0);
+        assert_eq!(Moments::new(1, 1).bit_correlation(), 0.0);
+    }
+}
@@ -0,0 +1,240 @@
+//! Online monitoring of the uniformity of generated values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::monitor::Monitor;
+//! use rand_bits::WeightClass;
+//!
+//! let mut monitor =
+//!     Monitor::new(thread_rng(), WeightClass::<u32>::new(5)).alarm(8.0, 1000, |deviation| {
+//!         panic!("broken generator: {}", deviation)
+//!     });
+//! let values: Vec<u32> = monitor.by_ref().take(10_000).collect();
+//! assert_eq!(values.len(), 10_000);
+//! assert!(monitor.entropy() > 4.9);
+//! ```
+
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::Word;
+
+/// The callback invoked when the deviation exceeds the threshold.
+type Callback = Box<dyn FnMut(f64)>;
+
+/// An iterator generating values with a distribution while tracking how uniformly their set
+/// bits are spread over the bit positions.
+///
+/// Every value generated by a correctly working distribution of values with a fixed number of
+/// set bits has its set bits at uniformly chosen positions. The monitor counts the set bits at
+/// every position and compares the counts with the uniform expectation by Pearson's chi-square
+/// test, corrected for the fixed number of set bits of every value. A misconfigured or broken
+/// generator shows up as a growing deviation, which can be watched periodically with an alarm
+/// callback.
+pub struct Monitor<R, D, T> {
+    rng: R,
+    distribution: D,
+    counts: Vec<u64>,
+    count: u64,
+    squares: u64,
+    alarm: Option<(f64, u64, Callback)>,
+    phantom: PhantomData<T>,
+}
+
+impl<R, D, T> Monitor<R, D, T>
+where
+    R: Rng,
+    D: rand::distributions::Distribution<T>,
+    T: Word,
+{
+    /// Create a new monitor of values generated with `distribution`.
+    pub fn new(rng: R, distribution: D) -> Self {
+        Self {
+            rng,
+            distribution,
+            counts: vec![0; T::BITS as usize],
+            count: 0,
+            squares: 0,
+            alarm: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Check the deviation every `interval` values and invoke `callback` with it whenever it
+    /// exceeds `threshold`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn alarm<F>(mut self, threshold: f64, interval: u64, callback: F) -> Self
+    where
+        F: FnMut(f64) + 'static,
+    {
+        assert!(interval > 0, "alarm interval is zero");
+        self.alarm = Some((threshold, interval, Box::new(callback)));
+        self
+    }
+
+    /// Return the number of generated values.
+    pub fn samples(&self) -> u64 {
+        self.count
+    }
+
+    /// Return Pearson's chi-square statistic of the counts of set bits at every position,
+    /// corrected for the set bits of a value excluding each other.
+    ///
+    /// With `k` of `n` positions set in every value the counts are negatively correlated, and the
+    /// plain statistic follows about `(n - k) / (n - 1)` times the chi-square distribution with
+    /// `n - 1` degrees of freedom. It is scaled back with the numbers of set bits of the
+    /// generated values, so for uniformly spread set bits the result follows the chi-square
+    /// distribution with `T::BITS - 1` degrees of freedom. Without any position to choose, i.e.
+    /// if every value has none or all bits set, it is zero.
+    pub fn chi_square(&self) -> f64 {
+        let total: u64 = self.counts.iter().sum();
+        let width = f64::from(T::BITS);
+        // the sum of k (n - k) over the generated values
+        let spread = width * total as f64 - self.squares as f64;
+        if spread <= 0.0 {
+            return 0.0;
+        }
+        let expected = total as f64 / width;
+        let plain: f64 = self
+            .counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+        plain * (width - 1.0) * total as f64 / spread
+    }
+
+    /// Return the chi-square statistic normalized to a standard score, i.e. the number of
+    /// standard deviations it lies above its expectation.
+    pub fn deviation(&self) -> f64 {
+        let freedom = f64::from(T::BITS - 1);
+        (self.chi_square() - freedom) / (2.0 * freedom).sqrt()
+    }
+
+    /// Return the Shannon entropy in bits of the position of a set bit, which is `log2(T::BITS)`
+    /// for uniformly spread set bits.
+    pub fn entropy(&self) -> f64 {
+        let total: u64 = self.counts.iter().sum();
+        self.counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum()
+    }
+}
+
+impl<R, D, T> Iterator for Monitor<R, D, T>
+where
+    R: Rng,
+    D: rand::distributions::Distribution<T>,
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let value = self.distribution.sample(&mut self.rng);
+        let mut rest = value.into_u128();
+        self.squares += u64::from(rest.count_ones().pow(2));
+        while rest != 0 {
+            self.counts[rest.trailing_zeros() as usize] += 1;
+            rest &= rest - 1;
+        }
+        self.count += 1;
+        if let Some((threshold, interval, _)) = self.alarm {
+            if self.count % interval == 0 {
+                let deviation = self.deviation();
+                if deviation > threshold {
+                    if let Some((_, _, callback)) = &mut self.alarm {
+                        callback(deviation);
+                    }
+                }
+            }
+        }
+        Some(value)
+    }
+}
+
+impl<R, D, T> Debug for Monitor<R, D, T>
+where
+    R: Debug,
+    D: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Monitor")
+            .field("rng", &self.rng)
+            .field("distribution", &self.distribution)
+            .field("counts", &self.counts)
+            .field("count", &self.count)
+            .field("squares", &self.squares)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::WeightClass;
+
+    struct Broken;
+
+    impl rand::distributions::Distribution<u16> for Broken {
+        fn sample<R>(&self, rng: &mut R) -> u16
+        where
+            R: Rng + ?Sized,
+        {
+            // the highest bit is never set
+            WeightClass::<u16>::new(3).choose(rng) & 0x7FFF | 1
+        }
+    }
+
+    #[test]
+    fn uniform() {
+        let mut monitor = Monitor::new(rand::thread_rng(), WeightClass::<u16>::new(3));
+        monitor.by_ref().take(20_000).for_each(drop);
+        assert_eq!(monitor.samples(), 20_000);
+        assert!(monitor.deviation() < 8.0);
+        assert!((monitor.entropy() - 4.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn unbiased() {
+        // the deviations of a correct generator average to zero even at half density, where the
+        // plain statistic lies about 1.9 standard deviations below
+        let runs = 50;
+        let total: f64 = (0..runs)
+            .map(|_| {
+                let mut monitor = Monitor::new(rand::thread_rng(), WeightClass::<u32>::new(16));
+                monitor.by_ref().take(500).for_each(drop);
+                monitor.deviation()
+            })
+            .sum();
+        assert!((total / f64::from(runs)).abs() < 0.6);
+
+        let mut monitor = Monitor::new(rand::thread_rng(), WeightClass::<u8>::new(8));
+        monitor.by_ref().take(100).for_each(drop);
+        assert_eq!(monitor.chi_square(), 0.0);
+    }
+
+    #[test]
+    fn broken() {
+        let alarms = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&alarms);
+        let mut monitor =
+            Monitor::new(rand::thread_rng(), Broken).alarm(8.0, 1000, move |_| counter.set(counter.get() + 1));
+        monitor.by_ref().take(20_000).for_each(drop);
+        assert!(monitor.deviation() > 8.0);
+        assert!(alarms.get() > 10);
+    }
+}
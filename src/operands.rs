@@ -0,0 +1,104 @@
+//! Tuples of operands with individually chosen numbers of set bits (ones).
+
+use rand::Rng;
+
+use crate::{Distribution, Standard};
+
+/// A distribution generating arrays of operands, each one with its own number of set bits.
+///
+/// It is meant for sweeping operand weights systematically, e.g. in data-dependent timing
+/// studies of arithmetic instructions. Tuples of up to four operands of different types are
+/// generated as well.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::{thread_rng, Rng};
+/// use rand_bits::Operands;
+///
+/// let pairs: Vec<[u32; 2]> = thread_rng()
+///     .sample_iter(Operands::new([3, 29]))
+///     .take(1000)
+///     .collect();
+/// for [a, b] in pairs {
+///     assert_eq!(a.count_ones(), 3);
+///     assert_eq!(b.count_ones(), 29);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Operands<const N: usize> {
+    bits: [u32; N],
+}
+
+impl<const N: usize> Operands<N> {
+    /// Create a new distribution of operands with the chosen numbers of set bits.
+    pub fn new(bits: [u32; N]) -> Self {
+        Self { bits }
+    }
+}
+
+impl<T, const N: usize> rand::distributions::Distribution<[T; N]> for Operands<N>
+where
+    Standard: Distribution<T>,
+{
+    /// Generate a random array of operands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any number of set bits is greater than the width of `T`.
+    fn sample<R>(&self, rng: &mut R) -> [T; N]
+    where
+        R: Rng + ?Sized,
+    {
+        self.bits.map(|bits| Standard.sample(rng, bits))
+    }
+}
+
+macro_rules! impl_tuple {
+    ($n:literal; $($t:ident $i:tt),+) => {
+        impl<$($t),+> rand::distributions::Distribution<($($t,)+)> for Operands<$n>
+        where
+            $(Standard: Distribution<$t>,)+
+        {
+            /// Generate a random tuple of operands, each element with its own number of set bits.
+            ///
+            /// # Panics
+            ///
+            /// Panics if any number of set bits is greater than the width of its element.
+            fn sample<R>(&self, rng: &mut R) -> ($($t,)+)
+            where
+                R: Rng + ?Sized,
+            {
+                ($(Distribution::<$t>::sample(&Standard, rng, self.bits[$i]),)+)
+            }
+        }
+    };
+}
+
+impl_tuple!(2; A 0, B 1);
+impl_tuple!(3; A 0, B 1, C 2);
+impl_tuple!(4; A 0, B 1, C 2, D 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operands() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u64::BITS {
+            let bits = [i, u64::BITS - i, i / 2];
+            let operands: [u64; 3] = rng.sample(Operands::new(bits));
+            for (n, i) in operands.iter().zip(bits) {
+                assert_eq!(n.count_ones(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn tuple() {
+        let mut rng = rand::thread_rng();
+        let (a, b, c): (u8, u32, u128) = rng.sample(Operands::new([3, 17, 100]));
+        assert_eq!((a.count_ones(), b.count_ones(), c.count_ones()), (3, 17, 100));
+    }
+}
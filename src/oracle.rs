@@ -0,0 +1,188 @@
+//! A deliberately simple reference sampler for differential testing, enabled by the `oracle`
+//! feature.
+//!
+//! The [`Oracle`] distribution does not share any code with the optimized generators of the
+//! crate and trades speed for being obviously correct, so fuzzers and downstream test suites can
+//! compare the optimized generators against it.
+//!
+//! The [`Standard`](crate::Standard) distribution behind [`RngBits::gen_bits`] matches the oracle
+//! only for `u8` (see [`Algorithm::Table`]); wider types match it with the other methods of
+//! [`Algorithm`] and with [`WeightClass`].
+//!
+//! [`Algorithm`]: crate::Algorithm
+//! [`Algorithm::Table`]: crate::Algorithm::Table
+//! [`RngBits::gen_bits`]: crate::RngBits::gen_bits
+//! [`WeightClass`]: crate::WeightClass
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::oracle::{self, Oracle};
+//! use rand_bits::{Distribution, RngBits};
+//!
+//! let mut rng = thread_rng();
+//! let x: u64 = Oracle.sample(&mut rng, 10);
+//! assert_eq!(x.count_ones(), 10);
+//! assert_eq!(oracle::enumerate::<u8>(7).len(), 8);
+//! let y: u8 = rng.gen_bits(3);
+//! assert!(oracle::enumerate::<u8>(3).contains(&y));
+//! ```
+
+use rand::Rng;
+
+use crate::{Distribution, Word};
+
+/// The widest type whose values are enumerated instead of sampled by positions.
+const MAX_ENUMERATED_BITS: u32 = 16;
+
+/// A reference distribution of uniformly chosen values with a fixed number of set bits.
+///
+/// Values of types up to 16 bits wide are chosen by index from the list of all values with the
+/// number of set bits; wider values get their set bits at positions drawn one by one from the
+/// positions left.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Oracle;
+
+impl<T> Distribution<T> for Oracle
+where
+    T: Word,
+{
+    /// Generate a random value of `T` with `bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    fn sample<R>(&self, rng: &mut R, bits: u32) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        assert!(bits <= T::BITS, "bits count out of range");
+        if T::BITS <= MAX_ENUMERATED_BITS {
+            let values = enumerate(bits);
+            return values[rng.gen_range(0..values.len())];
+        }
+        let mut positions: Vec<u32> = (0..T::BITS).collect();
+        let mut value = 0u128;
+        for _ in 0..bits {
+            let position = positions.swap_remove(rng.gen_range(0..positions.len()));
+            value |= 1 << position;
+        }
+        T::from_u128(value)
+    }
+}
+
+/// Return all values of `T` with `bits` set bits in ascending order, by checking every value of
+/// the type.
+///
+/// # Panics
+///
+/// Panics if `T` is wider than 16 bits or `bits` is greater than its width.
+pub fn enumerate<T>(bits: u32) -> Vec<T>
+where
+    T: Word,
+{
+    assert!(T::BITS <= MAX_ENUMERATED_BITS, "support too large to enumerate");
+    assert!(bits <= T::BITS, "bits count out of range");
+    let values = (0..(1u128 << T::BITS)).filter(|value| value.count_ones() == bits);
+    values.map(T::from_u128).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combination::binomial;
+    use crate::{Algorithm, RngBits, Standard, WeightClass};
+
+    /// Assert that the keys returned by `samples` calls of `sample` occur within six standard
+    /// deviations of their expected `probabilities`.
+    fn assert_frequencies<F>(samples: u32, probabilities: &[f64], mut sample: F)
+    where
+        F: FnMut() -> usize,
+    {
+        let mut counts = vec![0u32; probabilities.len()];
+        for _ in 0..samples {
+            counts[sample()] += 1;
+        }
+        for (key, (&count, &probability)) in counts.iter().zip(probabilities).enumerate() {
+            let expected = f64::from(samples) * probability;
+            let deviation = (expected * (1.0 - probability)).sqrt();
+            assert!(
+                (f64::from(count) - expected).abs() <= 6.0 * deviation + 1.0,
+                "key {:#x} counted {} times, expected {:.1}",
+                key,
+                count,
+                expected
+            );
+        }
+    }
+
+    /// Compare the numbers of set bits in the lowest byte of values of `T` with `bits` set bits
+    /// generated by the oracle and the uniform optimized generators with the hypergeometric
+    /// distribution they must follow.
+    fn low_byte<T>(bits: u32)
+    where
+        T: Word,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        let probabilities: Vec<f64> = (0..=u8::BITS)
+            .map(|j| {
+                let rest = bits.checked_sub(j).map_or(0, |rest| binomial(T::BITS - u8::BITS, rest));
+                (binomial(u8::BITS, j) * rest) as f64 / binomial(T::BITS, bits) as f64
+            })
+            .collect();
+        let low = |value: T| (value.into_u128() & 0xFF).count_ones() as usize;
+        assert_frequencies(5_000, &probabilities, || low(Oracle.sample(&mut rng, bits)));
+        for algorithm in [Algorithm::Floyd, Algorithm::Rejection, Algorithm::Unrank] {
+            assert_frequencies(5_000, &probabilities, || low(algorithm.sample(&mut rng, bits)));
+        }
+        let class = WeightClass::<T>::new(bits);
+        assert_frequencies(5_000, &probabilities, || low(class.choose(&mut rng)));
+    }
+
+    #[test]
+    fn enumerated() {
+        for i in 0..=u8::BITS {
+            let values = enumerate::<u8>(i);
+            assert_eq!(values.len() as u128, binomial(u8::BITS, i));
+            assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(values.iter().all(|value| WeightClass::new(i).contains(*value)));
+        }
+    }
+
+    #[test]
+    fn differential() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u128::BITS {
+            let n: u128 = Oracle.sample(&mut rng, i);
+            assert_eq!(n.count_ones(), i);
+        }
+        // Standard is uniform only for u8, so wider types are compared with the other generators
+        let (byte, word) = (|n: u8| usize::from(n), |n: u16| usize::from(n));
+        let mut probabilities = vec![0.0; 1 << u8::BITS];
+        enumerate::<u8>(3)
+            .into_iter()
+            .for_each(|value| probabilities[usize::from(value)] = 1.0 / 56.0);
+        assert_frequencies(28_000, &probabilities, || byte(Oracle.sample(&mut rng, 3)));
+        assert_frequencies(28_000, &probabilities, || byte(rng.gen_bits(3)));
+
+        let mut probabilities = vec![0.0; 1 << u16::BITS];
+        enumerate::<u16>(2)
+            .into_iter()
+            .for_each(|value| probabilities[usize::from(value)] = 1.0 / 120.0);
+        let class = WeightClass::<u16>::new(2);
+        assert_frequencies(2_400, &probabilities, || word(Oracle.sample(&mut rng, 2)));
+        for algorithm in [Algorithm::Floyd, Algorithm::Rejection, Algorithm::Unrank] {
+            assert_frequencies(24_000, &probabilities, || word(algorithm.sample(&mut rng, 2)));
+        }
+        assert_frequencies(24_000, &probabilities, || word(class.choose(&mut rng)));
+        assert_frequencies(24_000, &probabilities, || {
+            word(rng.gen_bits_where(2, 1, |_| true).unwrap())
+        });
+
+        low_byte::<u32>(8);
+        low_byte::<u64>(20);
+        low_byte::<u128>(32);
+    }
+}
@@ -0,0 +1,62 @@
+//! Verification of generated values, enabled by the `paranoid` feature.
+//!
+//! Every check compiles to nothing unless the feature is enabled.
+
+/// Check that `value` has exactly `bits` set bits, all of them within `mask`.
+///
+/// # Panics
+///
+/// Panics with a detailed report if the `paranoid` feature is enabled and the check fails.
+#[inline]
+pub(crate) fn weight(context: &str, value: u128, bits: u32, mask: u128) {
+    if cfg!(feature = "paranoid") && (value.count_ones() != bits || value & !mask != 0) {
+        panic!(
+            "paranoid check failed in {}:\n  value:    {:#b}\n  mask:     {:#b}\n  expected: {} set bits\n  found:    \
+             {} set bits, {:#b} outside the mask",
+            context,
+            value,
+            mask,
+            bits,
+            value.count_ones(),
+            value & !mask,
+        );
+    }
+}
+
+/// Check that a constraint described by `details` holds.
+///
+/// # Panics
+///
+/// Panics with a detailed report if the `paranoid` feature is enabled and the check fails.
+#[inline]
+pub(crate) fn constraint<F>(context: &str, holds: bool, details: F)
+where
+    F: FnOnce() -> String,
+{
+    if cfg!(feature = "paranoid") && !holds {
+        panic!("paranoid check failed in {}:\n  {}", context, details());
+    }
+}
+
+#[cfg(all(test, feature = "paranoid"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        weight("test", 0b1011, 3, 0xFF);
+        constraint("test", true, String::new);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected: 2 set bits")]
+    fn mismatch() {
+        weight("test", 0b1011, 2, 0xFF);
+    }
+
+    #[test]
+    #[should_panic(expected = "0b100000000 outside the mask")]
+    fn outside() {
+        weight("test", 0x10F, 5, 0xFF);
+    }
+}
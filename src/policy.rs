@@ -0,0 +1,188 @@
+//! Handling of out-of-range numbers of set bits.
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::{Distribution, Error, Standard, Word};
+
+/// The behavior when the requested number of set bits exceeds the width of the generated type.
+///
+/// Generic code computing numbers of set bits from runtime data can choose a non-panicking
+/// behavior instead of validating them upfront, and attach it to a [`Bits`] distribution.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::thread_rng;
+/// use rand_bits::{Error, Policy, RngBits};
+///
+/// let mut rng = thread_rng();
+/// assert_eq!(
+///     rng.gen_bits_with_policy::<u8>(12, Policy::Clamp),
+///     Ok(u8::MAX)
+/// );
+/// assert_eq!(
+///     rng.gen_bits_with_policy::<u8>(12, Policy::Error),
+///     Err(Error::BitsOutOfRange)
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Policy {
+    /// Panic, like all generators of this crate do.
+    Panic,
+    /// Saturate the number of set bits to the width of the type.
+    Clamp,
+    /// Return [`Error::BitsOutOfRange`].
+    Error,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::Panic
+    }
+}
+
+impl Policy {
+    /// Return the number of set bits to generate values of `T` with, according to the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BitsOutOfRange`] if `bits` is greater than the width of `T` and the
+    /// policy is [`Policy::Error`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T` and the policy is [`Policy::Panic`].
+    pub fn apply<T>(self, bits: u32) -> Result<u32, Error>
+    where
+        T: Word,
+    {
+        if bits <= T::BITS {
+            return Ok(bits);
+        }
+        match self {
+            Self::Panic => panic!("bits count out of range"),
+            Self::Clamp => Ok(T::BITS),
+            Self::Error => Err(Error::BitsOutOfRange),
+        }
+    }
+}
+
+/// A distribution of values of `T` with a fixed number of set bits and the [`Policy`] handling
+/// it if it is out of range.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::{thread_rng, Rng};
+/// use rand_bits::{Bits, Error, Policy};
+///
+/// let mut rng = thread_rng();
+/// assert_eq!(rng.sample(Bits::<u8>::new(12, Policy::Clamp)), Ok(u8::MAX));
+/// assert_eq!(
+///     rng.sample(Bits::<u8>::new(12, Policy::Error)),
+///     Err(Error::BitsOutOfRange)
+/// );
+/// let x: u32 = rng.sample(Bits::new(12, Policy::Error)).unwrap();
+/// assert_eq!(x.count_ones(), 12);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Bits<T> {
+    bits: u32,
+    policy: Policy,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Bits<T>
+where
+    T: Word,
+{
+    /// Create a new distribution of values with `bits` set bits, handled by `policy` if `bits` is
+    /// greater than the width of `T`.
+    pub fn new(bits: u32, policy: Policy) -> Self {
+        Self {
+            bits,
+            policy,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Return the requested number of set bits.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Return the policy handling an out-of-range number of set bits.
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+}
+
+impl<T> rand::distributions::Distribution<Result<T, Error>> for Bits<T>
+where
+    T: Word,
+    Standard: Distribution<T>,
+{
+    /// Generate a random value with the number of set bits given by the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BitsOutOfRange`] if the number of set bits is greater than the width of
+    /// `T` and the policy is [`Policy::Error`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of set bits is greater than the width of `T` and the policy is
+    /// [`Policy::Panic`].
+    fn sample<R>(&self, rng: &mut R) -> Result<T, Error>
+    where
+        R: Rng + ?Sized,
+    {
+        Standard.sample_with_policy(rng, self.bits, self.policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies() {
+        for policy in [Policy::Panic, Policy::Clamp, Policy::Error] {
+            for i in 0..=u16::BITS {
+                assert_eq!(policy.apply::<u16>(i), Ok(i));
+            }
+        }
+        assert_eq!(Policy::Clamp.apply::<u16>(17), Ok(16));
+        assert_eq!(Policy::Error.apply::<u16>(u32::MAX), Err(Error::BitsOutOfRange));
+    }
+
+    #[test]
+    fn distribution() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=2 * u16::BITS {
+            let n: u16 = rng.sample(Bits::new(i, Policy::Clamp)).unwrap();
+            assert_eq!(n.count_ones(), i.min(u16::BITS));
+            let values: Vec<Result<u16, Error>> = (&mut rng).sample_iter(Bits::new(i, Policy::Error)).take(4).collect();
+            match i <= u16::BITS {
+                true => assert!(values.iter().all(|n| n.unwrap().count_ones() == i)),
+                false => assert!(values.iter().all(|&n| n == Err(Error::BitsOutOfRange))),
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bits count out of range")]
+    fn sampled() {
+        rand::thread_rng()
+            .sample::<Result<u64, Error>, _>(Bits::new(65, Policy::Panic))
+            .ok();
+    }
+
+    #[test]
+    #[should_panic(expected = "bits count out of range")]
+    fn panic() {
+        let _ = Policy::default().apply::<u32>(33);
+    }
+}
@@ -0,0 +1,142 @@
+//! Quasi-random (low-discrepancy) sequences of values with a fixed number of set bits (ones).
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand_bits::quasi::VanDerCorput;
+//!
+//! // the first 16 points cover all 16 values with a single set bit
+//! let mut values: Vec<u16> = VanDerCorput::new(1).take(16).collect();
+//! values.sort();
+//! values.dedup();
+//! assert_eq!(values.len(), 16);
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::combination::{binomial, unrank};
+use crate::Word;
+
+/// An iterator over values with a fixed number of set bits following the base-2 van der Corput
+/// sequence over their ranks.
+///
+/// The `i`-th value is the one of rank `floor(phi(i) * len)`, where `phi` is the van der Corput
+/// radical inverse and `len` the number of values with the chosen number of set bits. The first
+/// `2^m` values are spread evenly over the ranks, so a modest number of them covers the values
+/// far more evenly than independent samples.
+#[derive(Clone, Copy, Debug)]
+pub struct VanDerCorput<T> {
+    bits: u32,
+    len: u128,
+    index: u64,
+    offset: u64,
+    phantom: PhantomData<T>,
+}
+
+impl<T> VanDerCorput<T>
+where
+    T: Word,
+{
+    /// Create a new sequence of values with `bits` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`.
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= T::BITS, "bits count out of range");
+        Self {
+            bits,
+            len: binomial(T::BITS, bits),
+            index: 0,
+            offset: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Rotate the sequence by a random offset (the Cranley-Patterson rotation), so independent
+    /// sequences can be compared while keeping their low discrepancy.
+    pub fn shifted<R>(mut self, rng: &mut R) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        self.offset = rng.gen();
+        self
+    }
+}
+
+impl<T> Iterator for VanDerCorput<T>
+where
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let point = self.index.reverse_bits().wrapping_add(self.offset);
+        self.index = self.index.wrapping_add(1);
+
+        // floor(point / 2^64 * len) without overflowing
+        let point = u128::from(point);
+        let rank = (self.len >> 64) * point + (((self.len & u128::from(u64::MAX)) * point) >> 64);
+        Some(T::from_u128(unrank(T::BITS, self.bits, rank)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage() {
+        for i in 0..=u16::BITS {
+            let len = binomial(u16::BITS, i) as usize;
+            let mut values: Vec<u16> = VanDerCorput::new(i).take(len).collect();
+            assert!(values.iter().all(|n| n.count_ones() == i));
+            values.sort_unstable();
+            values.dedup();
+            // every dyadic interval of ranks gets its share of the points
+            assert!(values.len() * 2 > len);
+        }
+    }
+
+    #[test]
+    fn discrepancy() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u16::BITS {
+            let len = binomial(u16::BITS, i) as u64;
+            // the first 2^m points are evenly spaced, so every value appears floor(2^m / len) or
+            // ceil(2^m / len) times, with or without a rotation
+            let points = (16 * len).next_power_of_two();
+            let (min, max) = (points / len, (points + len - 1) / len);
+            for sequence in [VanDerCorput::<u16>::new(i), VanDerCorput::new(i).shifted(&mut rng)] {
+                let mut counts = vec![0u64; 1 << u16::BITS];
+                let mut ones = [0u64; u16::BITS as usize];
+                for n in sequence.take(points as usize) {
+                    counts[usize::from(n)] += 1;
+                    for (bit, ones) in ones.iter_mut().enumerate() {
+                        *ones += u64::from(n >> bit & 1);
+                    }
+                }
+                let support = counts.iter().enumerate().filter(|&(n, _)| n.count_ones() == i);
+                assert!(support.clone().all(|(_, count)| (min..=max).contains(count)));
+                assert_eq!(support.map(|(_, count)| count).sum::<u64>(), points);
+                // every bit is set in a fraction of i / 16 of the points, up to about one point per
+                // value having it set
+                let expected = points * u64::from(i) / u64::from(u16::BITS);
+                let slack = binomial(u16::BITS - 1, i.saturating_sub(1)) as u64 + 1;
+                assert!(ones.iter().all(|&ones| ones.abs_diff(expected) <= slack));
+            }
+        }
+    }
+
+    #[test]
+    fn shifted() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u128::BITS {
+            for n in VanDerCorput::<u128>::new(i).shifted(&mut rng).take(16) {
+                assert_eq!(n.count_ones(), i);
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Recording and replaying of the random draws consumed by generators.
+//!
+//! A [`Recorder`] wraps a generator and logs every byte it hands out, marking where every sample
+//! ends. The [`Log`] can be saved with a failing test, serialized with [`Log::to_bytes`], and
+//! replayed later with [`Replay`], which reproduces exactly the same values without the original
+//! generator.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::replay::{Log, Recorder};
+//! use rand_bits::RngBits;
+//!
+//! let mut recorder = Recorder::new(thread_rng());
+//! let x: u64 = recorder.gen_bits(20);
+//! recorder.mark();
+//! let y: u64 = recorder.gen_bits(40);
+//! recorder.mark();
+//!
+//! let log = recorder.into_log();
+//! assert_eq!(log.len(), 2);
+//! assert_eq!(log.replay().gen_bits::<u64>(20), x);
+//! assert_eq!(log.replay_sample(1).gen_bits::<u64>(40), y);
+//!
+//! let saved = log.to_bytes();
+//! assert_eq!(Log::from_bytes(&saved), Some(log));
+//! ```
+
+use rand::RngCore;
+
+/// The random bytes consumed by generators, split into samples.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Log {
+    bytes: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl Log {
+    /// Read back a log serialized with [`Log::to_bytes`], or return `None` if `bytes` is not a
+    /// valid serialized log.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut words = bytes.chunks_exact(8).map(|word| {
+            let word = u64::from_le_bytes(word.try_into().unwrap());
+            usize::try_from(word).ok()
+        });
+        let count = words.next()??;
+        let header = count.checked_add(1)?.checked_mul(8)?;
+        if header > bytes.len() {
+            return None;
+        }
+        let ends = words.take(count).collect::<Option<Vec<_>>>()?;
+        let bytes = bytes[header..].to_vec();
+        // the samples follow one another within the recorded bytes
+        let ordered = ends.windows(2).all(|pair| pair[0] <= pair[1]);
+        if !ordered || ends.last().map_or(false, |&end| end > bytes.len()) {
+            return None;
+        }
+        Some(Self { bytes, ends })
+    }
+
+    /// Serialize the log, including the ends of the samples, e.g. to save it to a file.
+    ///
+    /// The format is the number of samples and the end of every sample, all as little-endian
+    /// 64-bit words, followed by the recorded bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 * (self.ends.len() + 1) + self.bytes.len());
+        bytes.extend_from_slice(&(self.ends.len() as u64).to_le_bytes());
+        for &end in &self.ends {
+            bytes.extend_from_slice(&(end as u64).to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+
+    /// Return all recorded bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Return the number of marked samples.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Return `true` if no sample has been marked.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Return the bytes consumed by the sample at `index`, or `None` if `index` is out of bounds.
+    pub fn sample(&self, index: usize) -> Option<&[u8]> {
+        let end = *self.ends.get(index)?;
+        let start = match index {
+            0 => 0,
+            index => self.ends[index - 1],
+        };
+        Some(&self.bytes[start..end])
+    }
+
+    /// Return a generator replaying all recorded bytes from the start.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay::new(&self.bytes)
+    }
+
+    /// Return a generator replaying the bytes consumed by the sample at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replay_sample(&self, index: usize) -> Replay<'_> {
+        Replay::new(self.sample(index).expect("sample index out of range"))
+    }
+}
+
+/// A generator wrapper logging every byte consumed from the wrapped generator.
+#[derive(Clone, Debug)]
+pub struct Recorder<R> {
+    rng: R,
+    log: Log,
+}
+
+impl<R> Recorder<R> {
+    /// Create a new recorder of `rng` with an empty log.
+    pub fn new(rng: R) -> Self {
+        Self {
+            rng,
+            log: Log::default(),
+        }
+    }
+
+    /// End the current sample, i.e. mark all bytes consumed since the previous mark as one
+    /// sample.
+    pub fn mark(&mut self) {
+        self.log.ends.push(self.log.bytes.len());
+    }
+
+    /// Return the log recorded so far.
+    pub fn log(&self) -> &Log {
+        &self.log
+    }
+
+    /// Return the recorded log, dropping the wrapped generator.
+    pub fn into_log(self) -> Log {
+        self.log
+    }
+}
+
+impl<R> RngCore for Recorder<R>
+where
+    R: RngCore,
+{
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        self.log.bytes.extend_from_slice(&value.to_le_bytes());
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.rng.next_u64();
+        self.log.bytes.extend_from_slice(&value.to_le_bytes());
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        self.log.bytes.extend_from_slice(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)?;
+        self.log.bytes.extend_from_slice(dest);
+        Ok(())
+    }
+}
+
+/// A generator handing out recorded bytes in the order they were consumed.
+///
+/// Generators consuming the bytes must make the same calls as during recording, otherwise the
+/// replayed values differ.
+#[derive(Clone, Debug)]
+pub struct Replay<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Replay<'a> {
+    /// Create a new generator replaying `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Return the number of bytes not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        self.fill_bytes(&mut bytes);
+        bytes
+    }
+}
+
+impl RngCore for Replay<'_> {
+    /// Return the next recorded 32-bit value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the log is exhausted.
+    fn next_u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take())
+    }
+
+    /// Return the next recorded 64-bit value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the log is exhausted.
+    fn next_u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take())
+    }
+
+    /// Fill `dest` with the next recorded bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the log is exhausted.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(dest.len() <= self.bytes.len(), "replay log exhausted");
+        let (head, tail) = self.bytes.split_at(dest.len());
+        dest.copy_from_slice(head);
+        self.bytes = tail;
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RngBits, WeightClass};
+
+    #[test]
+    fn replayed() {
+        let mut recorder = Recorder::new(rand::thread_rng());
+        let mut values = Vec::new();
+        for i in 0..=u128::BITS {
+            values.push(recorder.gen_bits::<u128>(i));
+            recorder.mark();
+        }
+        let class = WeightClass::<u64>::new(7);
+        let chosen = class.choose_multiple(&mut recorder, 10);
+        let mut bytes = [0u8; 5];
+        recorder.fill_bytes(&mut bytes);
+
+        let log = recorder.log().clone();
+        assert_eq!(log.len(), values.len());
+        let mut replay = log.replay();
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(replay.gen_bits::<u128>(i as u32), *value);
+            assert_eq!(log.replay_sample(i).gen_bits::<u128>(i as u32), *value);
+        }
+        assert_eq!(class.choose_multiple(&mut replay, 10), chosen);
+        let mut replayed = [0u8; 5];
+        replay.fill_bytes(&mut replayed);
+        assert_eq!(replayed, bytes);
+        assert_eq!(replay.remaining(), 0);
+    }
+
+    #[test]
+    fn serialized() {
+        let mut recorder = Recorder::new(rand::thread_rng());
+        let mut values = Vec::new();
+        for i in [3, 0, 60, 17] {
+            values.push(recorder.gen_bits::<u64>(i));
+            recorder.mark();
+        }
+        // bytes after the last mark are kept too
+        recorder.next_u32();
+        let log = recorder.into_log();
+
+        let copy = Log::from_bytes(&log.to_bytes()).unwrap();
+        assert_eq!(copy, log);
+        assert_eq!(copy.replay_sample(2).gen_bits::<u64>(60), values[2]);
+        assert_eq!(Log::from_bytes(&Log::default().to_bytes()), Some(Log::default()));
+
+        let bytes = log.to_bytes();
+        assert_eq!(Log::from_bytes(&bytes[..7]), None);
+        assert_eq!(Log::from_bytes(&bytes[..24]), None);
+        // the last sample ends past the recorded bytes
+        assert_eq!(Log::from_bytes(&bytes[..bytes.len() - 5]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "replay log exhausted")]
+    fn exhausted() {
+        Replay::new(&[1, 2, 3]).next_u32();
+    }
+}
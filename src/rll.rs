@@ -0,0 +1,272 @@
+//! Run-length-limited bit patterns, e.g. `(d, k)`-constrained sequences.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::{thread_rng, Rng};
+//! use rand_bits::rll::RunLength;
+//!
+//! // isolated ones separated by 2 to 7 zeros, with 10 set bits
+//! let rll = RunLength::<u64>::new(1..=1, 2..=7, Some(10)).unwrap();
+//! let x: u64 = thread_rng().sample(&rll);
+//! assert_eq!(x.count_ones(), 10);
+//! assert_eq!(x & (x >> 1), 0);
+//! ```
+
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::{paranoid, select, Error, Word};
+
+/// A distribution generating values whose runs of set bits (ones) and unset bits (zeros) have
+/// lengths within chosen bounds, optionally with an exact number of set bits.
+///
+/// The bounds apply to every run, including the ones at both ends of the value. Values are
+/// chosen uniformly among all values satisfying the constraints: the numbers of completions of
+/// every partial value are counted upfront by dynamic programming, and runs are then drawn from
+/// the lowest bit upward with probabilities proportional to those counts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RunLength<T> {
+    ones: RangeInclusive<u32>,
+    zeros: RangeInclusive<u32>,
+    weight: Option<u32>,
+    counts: Vec<u128>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> RunLength<T>
+where
+    T: Word,
+{
+    /// Create a new distribution of values with runs of ones within `ones` and runs of zeros
+    /// within `zeros`, with `weight` set bits if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Infeasible`] if no value satisfies the constraints.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is empty or allows runs of length zero.
+    pub fn new(ones: RangeInclusive<u32>, zeros: RangeInclusive<u32>, weight: Option<u32>) -> Result<Self, Error> {
+        assert!(!ones.is_empty() && *ones.start() > 0, "invalid run lengths of ones");
+        assert!(!zeros.is_empty() && *zeros.start() > 0, "invalid run lengths of zeros");
+        let width = T::BITS;
+        let mut rll = Self {
+            ones,
+            zeros,
+            weight,
+            counts: vec![0; ((width + 1) * (width + 1) * 2) as usize],
+            phantom: PhantomData,
+        };
+        for symbol in 0..2 {
+            let index = rll.index(0, 0, symbol);
+            rll.counts[index] = 1;
+        }
+        for len in 1..=width {
+            for bits in 0..=len {
+                for symbol in 0..2 {
+                    let count = rll
+                        .runs(len, bits, symbol)
+                        .map(|(run, bits)| rll.count(len - run, bits, 1 - symbol))
+                        .sum();
+                    let index = rll.index(len, bits, symbol);
+                    rll.counts[index] = count;
+                }
+            }
+        }
+        let feasible = match weight {
+            Some(bits) => bits <= width && (0..2).any(|symbol| rll.count(width, bits, symbol) > 0),
+            None => (0..=width).any(|bits| (0..2).any(|symbol| rll.count(width, bits, symbol) > 0)),
+        };
+        if feasible {
+            Ok(rll)
+        } else {
+            Err(Error::Infeasible)
+        }
+    }
+
+    fn index(&self, len: u32, bits: u32, symbol: u32) -> usize {
+        (((len * (T::BITS + 1)) + bits) * 2 + symbol) as usize
+    }
+
+    /// Return the number of `len`-bit values with `bits` set bits made of complete runs, the
+    /// lowest one of `symbol`.
+    fn count(&self, len: u32, bits: u32, symbol: u32) -> u128 {
+        if bits > len {
+            return 0;
+        }
+        self.counts[self.index(len, bits, symbol)]
+    }
+
+    /// Return the lengths of the runs of `symbol` fitting in `len` bits with `bits` set bits,
+    /// paired with the numbers of set bits left after them.
+    fn runs(&self, len: u32, bits: u32, symbol: u32) -> impl Iterator<Item = (u32, u32)> {
+        let bounds = if symbol == 1 { &self.ones } else { &self.zeros };
+        let (start, end) = (*bounds.start(), (*bounds.end()).min(len));
+        (start..=end).filter_map(move |run| {
+            match symbol {
+                1 => bits.checked_sub(run).map(|bits| (run, bits)),
+                _ => Some((run, bits)),
+            }
+        })
+    }
+}
+
+/// Return `true` with probability `a / (a + b)`, even if the sum overflows.
+fn choose_first<R>(rng: &mut R, a: u128, b: u128) -> bool
+where
+    R: Rng + ?Sized,
+{
+    match a.checked_add(b) {
+        Some(total) => rng.gen_range(0..total) < a,
+        // both counts are at most 2^127, so they are equal
+        None => rng.gen(),
+    }
+}
+
+impl<T> rand::distributions::Distribution<T> for RunLength<T>
+where
+    T: Word,
+{
+    fn sample<R>(&self, rng: &mut R) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        let width = T::BITS;
+        // the lowest run and the number of set bits
+        let totals = |symbol| {
+            match self.weight {
+                Some(bits) => self.count(width, bits, symbol),
+                None => (0..=width).map(|bits| self.count(width, bits, symbol)).sum(),
+            }
+        };
+        let mut symbol = u32::from(!choose_first(rng, totals(0), totals(1)));
+        let mut bits = match self.weight {
+            Some(bits) => bits,
+            None => {
+                let mut rank = rng.gen_range(0..totals(symbol));
+                (0..=width)
+                    .find(|&bits| {
+                        let count = self.count(width, bits, symbol);
+                        if rank < count {
+                            return true;
+                        }
+                        rank -= count;
+                        false
+                    })
+                    .unwrap()
+            },
+        };
+
+        let mut value = 0;
+        let mut len = width;
+        while len > 0 {
+            let mut rank = rng.gen_range(0..self.count(len, bits, symbol));
+            for (run, left) in self.runs(len, bits, symbol) {
+                let count = self.count(len - run, left, 1 - symbol);
+                if rank < count {
+                    if symbol == 1 {
+                        value |= select::ones(run) << (width - len);
+                    }
+                    len -= run;
+                    bits = left;
+                    break;
+                }
+                rank -= count;
+            }
+            symbol = 1 - symbol;
+        }
+        if let Some(bits) = self.weight {
+            paranoid::weight("RunLength", value, bits, select::ones(width));
+        }
+        if cfg!(feature = "paranoid") {
+            let run = self.violating_run(value);
+            paranoid::constraint("RunLength", run.is_none(), || {
+                format!("value {:#b} has a run {:?} out of bounds", value, run)
+            });
+        }
+        T::from_u128(value)
+    }
+}
+
+impl<T> RunLength<T>
+where
+    T: Word,
+{
+    /// Return the first run of `value`, as its symbol and length, whose length is out of bounds.
+    fn violating_run(&self, value: u128) -> Option<(u32, u32)> {
+        let mut position = 0;
+        while position < T::BITS {
+            let symbol = (value >> position) as u32 & 1;
+            let rest = if symbol == 1 { !value } else { value };
+            let len = ((rest >> position).trailing_zeros()).min(T::BITS - position);
+            let bounds = if symbol == 1 { &self.ones } else { &self.zeros };
+            if !bounds.contains(&len) {
+                return Some((symbol, len));
+            }
+            position += len;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the lengths of the runs of `value`, lowest first, paired with their symbols.
+    fn runs(value: u16) -> Vec<(u32, u32)> {
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for position in 0..u16::BITS {
+            let symbol = u32::from(value >> position) & 1;
+            match runs.last_mut() {
+                Some((last, len)) if *last == symbol => *len += 1,
+                _ => runs.push((symbol, 1)),
+            }
+        }
+        runs
+    }
+
+    #[test]
+    fn constrained() {
+        let mut rng = rand::thread_rng();
+        for weight in [None, Some(4), Some(6)] {
+            let rll = RunLength::<u16>::new(1..=2, 2..=3, weight).unwrap();
+            for _ in 0..100 {
+                let n: u16 = rng.sample(&rll);
+                assert!(weight.map_or(true, |bits| n.count_ones() == bits));
+                for (symbol, len) in runs(n) {
+                    assert!(if symbol == 1 { 1..=2 } else { 2..=3 }.contains(&len));
+                }
+            }
+        }
+        assert_eq!(RunLength::<u16>::new(1..=1, 1..=1, Some(9)), Err(Error::Infeasible));
+    }
+
+    #[test]
+    fn uniform() {
+        let mut rng = rand::thread_rng();
+        let rll = RunLength::<u16>::new(1..=2, 1..=2, Some(8)).unwrap();
+        let valid: Vec<u16> = (0..=u16::MAX)
+            .filter(|&n| n.count_ones() == 8 && runs(n).iter().all(|&(_, len)| len <= 2))
+            .collect();
+        let mut counts = std::collections::HashMap::new();
+        let trials = valid.len() * 40;
+        for _ in 0..trials {
+            *counts.entry(rng.sample(&rll)).or_insert(0u32) += 1;
+        }
+        assert_eq!(counts.len(), valid.len());
+        assert!(counts.values().all(|&count| (10..=80).contains(&count)));
+    }
+
+    #[test]
+    fn unconstrained() {
+        let mut rng = rand::thread_rng();
+        let rll = RunLength::<u128>::new(1..=128, 1..=128, None).unwrap();
+        let total: u32 = (0..1000).map(|_| rng.sample(&rll).count_ones()).sum();
+        assert!((60_000..68_000).contains(&total));
+    }
+}
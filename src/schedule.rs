@@ -0,0 +1,91 @@
+//! Sequences of values following a schedule of numbers of set bits (ones).
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::schedule::Scheduled;
+//!
+//! // a triangle wave of densities
+//! let ramp = (0..=32).chain((0..32).rev());
+//! let values: Vec<u32> = Scheduled::new(thread_rng(), ramp).collect();
+//! assert_eq!(values.len(), 65);
+//! assert_eq!(values[10].count_ones(), 10);
+//! assert_eq!(values[50].count_ones(), 14);
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::{Distribution, Standard};
+
+/// An iterator generating one value per step of a schedule, each one with exactly the number of
+/// set bits the schedule gives for that step.
+///
+/// The schedule is any iterator of numbers of set bits, e.g. a ramp, a sampled sine wave or a
+/// recorded trace, and the sequence ends with it. Closures of the step index can be turned into
+/// a schedule with `(0..).map(f)`.
+#[derive(Clone, Debug)]
+pub struct Scheduled<R, I, T> {
+    rng: R,
+    schedule: I,
+    phantom: PhantomData<T>,
+}
+
+impl<R, I, T> Scheduled<R, I, T>
+where
+    R: Rng,
+    I: Iterator<Item = u32>,
+    Standard: Distribution<T>,
+{
+    /// Create a new sequence following `schedule`.
+    pub fn new<S>(rng: R, schedule: S) -> Self
+    where
+        S: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            rng,
+            schedule: schedule.into_iter(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<R, I, T> Iterator for Scheduled<R, I, T>
+where
+    R: Rng,
+    I: Iterator<Item = u32>,
+    Standard: Distribution<T>,
+{
+    type Item = T;
+
+    /// Generate the value of the next step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scheduled number of set bits is greater than the width of `T`.
+    fn next(&mut self) -> Option<T> {
+        let bits = self.schedule.next()?;
+        Some(Standard.sample(&mut self.rng, bits))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.schedule.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sine() {
+        let schedule = (0..100).map(|step| (32.0 + 31.0 * (f64::from(step) / 10.0).sin()).round() as u32);
+        let values: Vec<u64> = Scheduled::new(rand::thread_rng(), schedule.clone()).collect();
+        assert_eq!(values.len(), 100);
+        for (n, i) in values.into_iter().zip(schedule) {
+            assert_eq!(n.count_ones(), i);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Selection of set bits (ones) among the positions allowed by a mask.
+
+use rand::Rng;
+
+use crate::paranoid;
+
+/// Return a value with the lowest `width` bits set.
+pub(crate) fn ones(width: u32) -> u128 {
+    match width {
+        0 => 0,
+        width => u128::MAX >> (u128::BITS - width),
+    }
+}
+
+/// Return a uniformly chosen value with `bits` set bits, all of them within `mask`.
+///
+/// # Panics
+///
+/// Panics if `mask` has less than `bits` set bits.
+pub(crate) fn sample<R>(rng: &mut R, mask: u128, bits: u32) -> u128
+where
+    R: Rng + ?Sized,
+{
+    let width = mask.count_ones();
+    assert!(bits <= width, "bits count out of range");
+    let value = deposit(compact(rng, width, bits), mask);
+    paranoid::weight("select::sample", value, bits, mask);
+    value
+}
+
+/// Return a uniformly chosen `width`-bit value with `bits` set bits.
+fn compact<R>(rng: &mut R, width: u32, bits: u32) -> u128
+where
+    R: Rng + ?Sized,
+{
+    // choose the smaller of the set and the unset positions
+    if bits > width / 2 {
+        return compact(rng, width, width - bits) ^ ones(width);
+    }
+
+    // Floyd's algorithm
+    let mut value = 0u128;
+    for j in (width - bits)..width {
+        let position = rng.gen_range(0..=j);
+        if value & (1 << position) == 0 {
+            value |= 1 << position;
+        } else {
+            value |= 1 << j;
+        }
+    }
+    value
+}
+
+/// Scatter the lowest bits of `value` into the positions of the set bits of `mask`.
+fn deposit(value: u128, mask: u128) -> u128 {
+    #[cfg(all(feature = "bmi2", target_arch = "x86_64"))]
+    if let Some(result) = crate::bmi2::deposit(value, mask) {
+        return result;
+    }
+    scatter(value, mask)
+}
+
+/// Scatter the lowest bits of `value` into the positions of the set bits of `mask`, bit by bit.
+fn scatter(mut value: u128, mut mask: u128) -> u128 {
+    let mut result = 0;
+    while mask != 0 && value != 0 {
+        let lowest = mask & mask.wrapping_neg();
+        if value & 1 == 1 {
+            result |= lowest;
+        }
+        value >>= 1;
+        mask ^= lowest;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masked() {
+        let mut rng = rand::thread_rng();
+        let masks = [0, 0xFF, 0xF0F0, 0x8000_0000_0000_0001, u128::MAX, u128::MAX << 100];
+        for mask in masks {
+            for i in 0..=mask.count_ones() {
+                let n = sample(&mut rng, mask, i);
+                assert_eq!(n.count_ones(), i);
+                assert_eq!(n & !mask, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn deposited() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(deposit(0b101, 0xF0F0), 0x50);
+        assert_eq!(deposit(u128::MAX, u128::MAX << 60), u128::MAX << 60);
+        for _ in 0..1_000 {
+            let (value, mask) = (rng.gen(), rng.gen());
+            assert_eq!(deposit(value, mask), scatter(value, mask));
+        }
+    }
+}
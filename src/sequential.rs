@@ -0,0 +1,171 @@
+//! Sequential random sampling of sorted indices (Vitter's methods A and D).
+
+use rand::Rng;
+
+/// The ratio of the population size to the sample size below which method A is used.
+const ALPHA_INVERSE: u128 = 13;
+
+/// Return `amount` distinct indices less than `len` chosen uniformly, in ascending order.
+///
+/// Indices are produced in a single pass by drawing the gaps between them, which takes
+/// `O(amount)` expected time regardless of `len`. If `len` is less than `amount`, all indices are
+/// returned.
+///
+/// Gaps are drawn in floating point. Beyond `2^53` the low bits of a gap are filled uniformly
+/// within the floating-point cell it falls into, so the sample stays uniform at every scale.
+pub(crate) fn sample<R>(rng: &mut R, len: u128, amount: u128) -> Vec<u128>
+where
+    R: Rng + ?Sized,
+{
+    let mut indices = Vec::with_capacity(amount.min(len) as usize);
+    let mut n = amount.min(len);
+    let mut population = len;
+    let mut current = 0;
+
+    // method D
+    let mut n_real = n as f64;
+    let mut n_inv = n_real.recip();
+    let mut v_prime = open_unit(rng).powf(n_inv);
+    let mut qu1 = population - n + 1;
+    let mut threshold = ALPHA_INVERSE * n;
+    while n > 1 && threshold < population {
+        let population_real = population as f64;
+        let qu1_real = qu1 as f64;
+        let n_min1_inv = (n_real - 1.0).recip();
+        let skip = loop {
+            let (x, skip) = loop {
+                let x = population_real * (1.0 - v_prime);
+                let skip = floor(rng, x);
+                if skip < qu1 {
+                    break (x, skip);
+                }
+                v_prime = open_unit(rng).powf(n_inv);
+            };
+            let u = open_unit(rng);
+            let skip_real = skip as f64;
+            let y1 = (u * population_real / qu1_real).powf(n_min1_inv);
+            v_prime = y1 * (1.0 - x / population_real) * (qu1_real / (qu1_real - skip_real));
+            if v_prime <= 1.0 {
+                break skip;
+            }
+
+            let mut y2 = 1.0;
+            let mut top = population_real - 1.0;
+            let (mut bottom, limit) = if n - 1 > skip {
+                (population_real - n_real, population - skip)
+            } else {
+                (population_real - skip_real - 1.0, qu1)
+            };
+            for _ in limit..population {
+                y2 = y2 * top / bottom;
+                top -= 1.0;
+                bottom -= 1.0;
+            }
+            if population_real / (population_real - x) >= y1 * y2.powf(n_min1_inv) {
+                v_prime = open_unit(rng).powf(n_min1_inv);
+                break skip;
+            }
+            v_prime = open_unit(rng).powf(n_inv);
+        };
+
+        current += skip;
+        indices.push(current);
+        current += 1;
+        population -= skip + 1;
+        n -= 1;
+        n_real -= 1.0;
+        n_inv = n_min1_inv;
+        qu1 -= skip;
+        threshold -= ALPHA_INVERSE;
+    }
+
+    // method A
+    let mut top = (population - n) as f64;
+    let mut population_real = population as f64;
+    while n > 1 {
+        let v = rng.gen::<f64>();
+        let mut skip = 0;
+        let mut quot = top / population_real;
+        while quot > v {
+            skip += 1;
+            top -= 1.0;
+            population_real -= 1.0;
+            quot = quot * top / population_real;
+        }
+        current += skip;
+        indices.push(current);
+        current += 1;
+        population -= skip + 1;
+        population_real -= 1.0;
+        n -= 1;
+    }
+    if n == 1 {
+        indices.push(current + rng.gen_range(0..population));
+    }
+    indices
+}
+
+/// Return a uniform random number in `(0, 1]`.
+fn open_unit<R>(rng: &mut R) -> f64
+where
+    R: Rng + ?Sized,
+{
+    1.0 - rng.gen::<f64>()
+}
+
+/// Return `floor(x)`, filling the bits below the precision of `x` uniformly.
+fn floor<R>(rng: &mut R, x: f64) -> u128
+where
+    R: Rng + ?Sized,
+{
+    const EXACT: f64 = (1u64 << f64::MANTISSA_DIGITS) as f64;
+    if x < EXACT {
+        return x as u128;
+    }
+    let exponent = ((x.to_bits() >> 52) & 0x7FF) as u32 - 1023;
+    let ulp = 1u128 << (exponent + 1 - f64::MANTISSA_DIGITS);
+    (x as u128).saturating_add(rng.gen_range(0..ulp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted() {
+        let mut rng = rand::thread_rng();
+        for (len, amount) in [
+            (0, 0),
+            (0, 5),
+            (5, 5),
+            (10, 3),
+            (1000, 50),
+            (1000, 999),
+            (1 << 100, 1000),
+            (u128::MAX, 10),
+        ] {
+            let indices = sample(&mut rng, len, amount);
+            assert_eq!(indices.len() as u128, amount.min(len));
+            assert!(indices.windows(2).all(|w| w[0] < w[1]));
+            assert!(indices.iter().all(|&index| index < len));
+        }
+    }
+
+    #[test]
+    fn uniform() {
+        let mut rng = rand::thread_rng();
+        for (len, amount) in [(20, 5), (200, 5)] {
+            let mut counts = vec![0u32; len as usize];
+            let trials = 20_000;
+            for _ in 0..trials {
+                for index in sample(&mut rng, len, amount) {
+                    counts[index as usize] += 1;
+                }
+            }
+            let expected = f64::from(trials) * amount as f64 / len as f64;
+            for count in counts {
+                assert!((f64::from(count) - expected).abs() < expected * 0.25);
+            }
+        }
+    }
+}
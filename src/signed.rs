@@ -0,0 +1,326 @@
+//! Signed values with a fixed number of set bits (ones) in a chosen representation.
+//!
+//! The number of set bits of a signed value depends on how it is encoded: `-1` has all bits set
+//! in two's complement, one bit besides the sign in sign-magnitude, and only the sign bit unset
+//! in one's complement. [`Signed`] constrains the encoding in the chosen [`Representation`] and
+//! returns the numeric value as a native integer.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::{thread_rng, Rng};
+//! use rand_bits::signed::{Representation, Sign, Signed};
+//!
+//! let signed = Signed::<i16>::new(Representation::SignMagnitude, Sign::Negative, 3).unwrap();
+//! let x: i16 = thread_rng().sample(&signed);
+//! assert!(x < 0);
+//! assert_eq!(x.unsigned_abs().count_ones(), 2);
+//! assert_eq!(Representation::SignMagnitude.encode(x).count_ones(), 3);
+//! ```
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::{paranoid, select, Error};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A signed integer type that can be generated with a fixed number of set bits (ones).
+///
+/// This trait is sealed and implemented for `i8`, `i16`, `i32`, `i64` and `i128`.
+pub trait SignedWord: Copy + Debug + Default + Eq + Hash + Ord + private::Sealed {
+    /// The size of this integer type in bits.
+    const BITS: u32;
+
+    /// Convert `value` into `Self`, truncating the high bits.
+    fn from_i128(value: i128) -> Self;
+
+    /// Convert `self` into `i128`.
+    fn into_i128(self) -> i128;
+}
+
+macro_rules! impl_signed_word {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl SignedWord for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                fn from_i128(value: i128) -> Self {
+                    value as $t
+                }
+
+                fn into_i128(self) -> i128 {
+                    self as i128
+                }
+            }
+        )*
+    };
+}
+
+impl_signed_word!(i8, i16, i32, i64, i128);
+
+/// An encoding of signed values.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Representation {
+    /// Negative values are the complements of their magnitudes plus one, as native integers.
+    TwosComplement,
+    /// Negative values are the complements of their magnitudes.
+    OnesComplement,
+    /// Negative values are their magnitudes with the sign bit set.
+    SignMagnitude,
+}
+
+impl Representation {
+    /// Return the encoding of `value`, as wide as `T`.
+    ///
+    /// Zero is always encoded as positive zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is `T::MIN` in one's complement or sign-magnitude, which cannot
+    /// represent it.
+    pub fn encode<T>(self, value: T) -> u128
+    where
+        T: SignedWord,
+    {
+        let value = value.into_i128();
+        let magnitude = value.unsigned_abs();
+        if self != Self::TwosComplement {
+            assert!(magnitude < sign(T::BITS), "value out of range");
+        }
+        match (self, value < 0) {
+            (_, false) => magnitude,
+            (Self::TwosComplement, true) => value as u128 & select::ones(T::BITS),
+            (Self::OnesComplement, true) => !magnitude & select::ones(T::BITS),
+            (Self::SignMagnitude, true) => sign(T::BITS) | magnitude,
+        }
+    }
+
+    /// Return the value encoded as the lowest `T::BITS` bits of `encoding`.
+    ///
+    /// Negative zero is decoded as zero.
+    pub fn decode<T>(self, encoding: u128) -> T
+    where
+        T: SignedWord,
+    {
+        let encoding = encoding & select::ones(T::BITS);
+        let negative = encoding & sign(T::BITS) != 0;
+        let value = match (self, negative) {
+            (_, false) => encoding as i128,
+            (Self::TwosComplement, true) => (encoding | !select::ones(T::BITS)) as i128,
+            (Self::OnesComplement, true) => -((!encoding & select::ones(T::BITS)) as i128),
+            (Self::SignMagnitude, true) => -((encoding ^ sign(T::BITS)) as i128),
+        };
+        T::from_i128(value)
+    }
+
+    /// Return the encoding of negative zero in `width` bits, if the representation has one.
+    fn negative_zero(self, width: u32) -> Option<u128> {
+        match self {
+            Self::TwosComplement => None,
+            Self::OnesComplement => Some(select::ones(width)),
+            Self::SignMagnitude => Some(sign(width)),
+        }
+    }
+}
+
+/// A constraint of the sign bit.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Sign {
+    /// The sign bit is set or unset like any other bit.
+    Any,
+    /// The sign bit is unset, making every value non-negative.
+    NonNegative,
+    /// The sign bit is set, making every value negative.
+    Negative,
+}
+
+impl Sign {
+    /// Return `true` if the constraint admits the sign bit being `set`.
+    fn admits(self, set: bool) -> bool {
+        match self {
+            Self::Any => true,
+            Self::NonNegative => !set,
+            Self::Negative => set,
+        }
+    }
+}
+
+/// A distribution of signed values whose encodings have a fixed number of set bits.
+///
+/// Values are chosen uniformly among all values of `T` whose encodings in the representation
+/// have the number of set bits and the sign bit as constrained. Negative zero never occurs, since
+/// it is indistinguishable from zero as a native integer.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Signed<T> {
+    representation: Representation,
+    sign: Sign,
+    bits: u32,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Signed<T>
+where
+    T: SignedWord,
+{
+    /// Create a new distribution of values with `bits` set bits in `representation` and the sign
+    /// bit constrained by `sign`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::BitsOutOfRange`] if `bits` is greater than the width of `T`, and
+    /// [`Error::Infeasible`] if no value satisfies the constraints.
+    pub fn new(representation: Representation, sign: Sign, bits: u32) -> Result<Self, Error> {
+        if bits > T::BITS {
+            return Err(Error::BitsOutOfRange);
+        }
+        let signed = Self {
+            representation,
+            sign,
+            bits,
+            phantom: PhantomData,
+        };
+        let low = T::BITS - 1;
+        let candidates = match sign {
+            Sign::Any => binomial(T::BITS, bits),
+            Sign::NonNegative => binomial(low, bits),
+            Sign::Negative => bits.checked_sub(1).map_or(0, |bits| binomial(low, bits)),
+        };
+        let excluded = representation
+            .negative_zero(T::BITS)
+            .filter(|zero| sign != Sign::NonNegative && zero.count_ones() == bits);
+        match candidates > u128::from(excluded.is_some()) {
+            true => Ok(signed),
+            false => Err(Error::Infeasible),
+        }
+    }
+
+    /// Return the representation.
+    pub fn representation(&self) -> Representation {
+        self.representation
+    }
+
+    /// Return the constraint of the sign bit.
+    pub fn sign(&self) -> Sign {
+        self.sign
+    }
+
+    /// Return the number of set bits of encodings.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+}
+
+impl<T> rand::distributions::Distribution<T> for Signed<T>
+where
+    T: SignedWord,
+{
+    fn sample<R>(&self, rng: &mut R) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        let low = select::ones(T::BITS - 1);
+        let negative_zero = self.representation.negative_zero(T::BITS);
+        loop {
+            let encoding = match self.sign {
+                Sign::Any => select::sample(rng, select::ones(T::BITS), self.bits),
+                Sign::NonNegative => select::sample(rng, low, self.bits),
+                Sign::Negative => sign(T::BITS) | select::sample(rng, low, self.bits - 1),
+            };
+            // at most one of at least two candidates is rejected
+            if Some(encoding) != negative_zero {
+                let value = self.representation.decode(encoding);
+                let encoded = self.representation.encode(value);
+                paranoid::weight("Signed", encoded, self.bits, select::ones(T::BITS));
+                paranoid::constraint("Signed", self.sign.admits(encoded >> (T::BITS - 1) == 1), || {
+                    format!("encoding {:#b} breaks the sign constraint {:?}", encoded, self.sign)
+                });
+                return value;
+            }
+        }
+    }
+}
+
+/// Return the sign bit of `width`-bit values.
+fn sign(width: u32) -> u128 {
+    1 << (width - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const REPRESENTATIONS: [Representation; 3] = [
+        Representation::TwosComplement,
+        Representation::OnesComplement,
+        Representation::SignMagnitude,
+    ];
+
+    #[test]
+    fn encoded() {
+        for representation in REPRESENTATIONS {
+            for value in (i8::MIN + 1)..=i8::MAX {
+                let encoding = representation.encode(value);
+                assert!(encoding <= 0xFF);
+                assert_eq!(representation.decode::<i8>(encoding), value);
+            }
+            assert_eq!(representation.decode::<i128>(representation.encode(-1i128)), -1);
+        }
+        assert_eq!(Representation::TwosComplement.encode(-1i8), 0xFF);
+        assert_eq!(Representation::OnesComplement.encode(-1i8), 0xFE);
+        assert_eq!(Representation::SignMagnitude.encode(-1i8), 0x81);
+        assert_eq!(Representation::SignMagnitude.encode(i128::MAX), u128::MAX >> 1);
+        assert_eq!(Representation::OnesComplement.decode::<i8>(0xFF), 0);
+        assert_eq!(Representation::SignMagnitude.decode::<i8>(0x80), 0);
+    }
+
+    #[test]
+    fn exhaustive() {
+        let mut rng = rand::thread_rng();
+        for representation in REPRESENTATIONS {
+            for sign in [Sign::Any, Sign::NonNegative, Sign::Negative] {
+                for i in 0..=i8::BITS {
+                    // all values satisfying the constraints, negative zero excluded
+                    let expected: HashSet<i8> = ((i8::MIN + 1)..=i8::MAX)
+                        .chain((representation == Representation::TwosComplement).then(|| i8::MIN))
+                        .filter(|&value| representation.encode(value).count_ones() == i)
+                        .filter(|&value| {
+                            match sign {
+                                Sign::Any => true,
+                                Sign::NonNegative => value >= 0,
+                                Sign::Negative => value < 0,
+                            }
+                        })
+                        .collect();
+                    match Signed::<i8>::new(representation, sign, i) {
+                        Ok(signed) => {
+                            let generated: HashSet<i8> = (0..2_000).map(|_| rng.sample(signed)).collect();
+                            assert_eq!(generated, expected);
+                        },
+                        Err(error) => {
+                            assert_eq!(error, Error::Infeasible);
+                            assert!(expected.is_empty());
+                        },
+                    }
+                }
+            }
+        }
+        let signed = Signed::<i128>::new(Representation::OnesComplement, Sign::Any, 100).unwrap();
+        let x: i128 = rng.sample(signed);
+        assert_eq!(Representation::OnesComplement.encode(x).count_ones(), 100);
+        assert_eq!(
+            Signed::<i8>::new(Representation::TwosComplement, Sign::Any, 9),
+            Err(Error::BitsOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Sampling with a number of set bits (ones) following an empirical histogram.
+
+use std::ops::AddAssign;
+
+use rand::distributions::uniform::{SampleBorrow, SampleUniform};
+use rand::distributions::{WeightedError, WeightedIndex};
+use rand::Rng;
+
+use crate::{Distribution, Standard};
+
+/// A distribution generating values whose numbers of set bits (ones) follow an empirical
+/// histogram, e.g. one measured from real traffic.
+///
+/// The `i`-th histogram entry is the relative frequency of values with `i` set bits. Once the
+/// number of set bits is chosen, the value is generated by the [`Standard`] distribution.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::{thread_rng, Rng};
+/// use rand_bits::WeightSpectrum;
+///
+/// let spectrum = WeightSpectrum::new([0, 10, 0, 30]).unwrap();
+/// let x: u16 = thread_rng().sample(&spectrum);
+/// assert!(x.count_ones() == 1 || x.count_ones() == 3);
+/// ```
+pub struct WeightSpectrum<X>
+where
+    X: SampleUniform + PartialOrd,
+{
+    index: WeightedIndex<X>,
+}
+
+impl<X> WeightSpectrum<X>
+where
+    X: SampleUniform + PartialOrd,
+{
+    /// Create a new distribution from a histogram over the numbers of set bits.
+    ///
+    /// Fails under the same conditions as [`WeightedIndex::new`], i.e. when the histogram is
+    /// empty, contains a negative entry or all entries are zero.
+    pub fn new<I>(histogram: I) -> Result<Self, WeightedError>
+    where
+        I: IntoIterator,
+        I::Item: SampleBorrow<X>,
+        X: for<'a> AddAssign<&'a X> + Clone + Default,
+    {
+        let index = WeightedIndex::new(histogram)?;
+        Ok(Self { index })
+    }
+}
+
+impl<T, X> rand::distributions::Distribution<T> for WeightSpectrum<X>
+where
+    Standard: Distribution<T>,
+    X: SampleUniform + PartialOrd,
+{
+    /// Generate a random value of `T` with a number of set bits drawn from the histogram.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the drawn number of set bits exceeds the width of `T`.
+    fn sample<R>(&self, rng: &mut R) -> T
+    where
+        R: Rng + ?Sized,
+    {
+        let bits = rng.sample(&self.index) as u32;
+        Standard.sample(rng, bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let mut histogram = [0u32; 9];
+            histogram[i as usize] = 1;
+            let spectrum = WeightSpectrum::new(histogram).unwrap();
+            for _ in 0..16 {
+                let n: u8 = rng.sample(&spectrum);
+                assert_eq!(n.count_ones(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn mixed() {
+        let mut rng = rand::thread_rng();
+        let spectrum = WeightSpectrum::new([0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap();
+        for _ in 0..64 {
+            let n: u64 = rng.sample(&spectrum);
+            assert!(n.count_ones() == 2 || n.count_ones() == 9);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(WeightSpectrum::<u32>::new([0, 0]).is_err());
+        assert!(WeightSpectrum::<u32>::new(Vec::<u32>::new()).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Statistics of generated values: weight histograms, per-position counts and correlations.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::stats::Stats;
+//! use rand_bits::RngBits;
+//!
+//! let mut rng = thread_rng();
+//! let mut stats = Stats::<u16>::new();
+//! stats.extend((0..1000).map(|_| rng.gen_bits::<u16>(4)));
+//! assert_eq!(stats.count(), 1000);
+//! assert_eq!(stats.weights()[4], 1000);
+//! assert_eq!(stats.positions().iter().sum::<u64>(), 4000);
+//! println!("{}", stats);
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+
+use crate::Word;
+
+/// A collector of statistics of values of `T`.
+///
+/// It counts the values with every number of set bits, the values with every bit position set
+/// and the values with every pair of bit positions set, from which the correlations between
+/// positions are derived. Its [`Display`] implementation prints a summary report.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stats<T> {
+    count: u64,
+    weights: Vec<u64>,
+    positions: Vec<u64>,
+    pairs: Vec<u64>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Stats<T>
+where
+    T: Word,
+{
+    /// Create a new collector without any values.
+    pub fn new() -> Self {
+        let width = T::BITS as usize;
+        Self {
+            count: 0,
+            weights: vec![0; width + 1],
+            positions: vec![0; width],
+            pairs: vec![0; width * width],
+            phantom: PhantomData,
+        }
+    }
+
+    /// Add `value` to the statistics.
+    pub fn add(&mut self, value: T) {
+        let width = T::BITS as usize;
+        let value = value.into_u128();
+        self.count += 1;
+        self.weights[value.count_ones() as usize] += 1;
+        let mut rest = value;
+        while rest != 0 {
+            let i = rest.trailing_zeros() as usize;
+            rest &= rest - 1;
+            self.positions[i] += 1;
+            let mut others = value;
+            while others != 0 {
+                let j = others.trailing_zeros() as usize;
+                others &= others - 1;
+                self.pairs[i * width + j] += 1;
+            }
+        }
+    }
+
+    /// Return the number of added values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Return the numbers of values with `0..=T::BITS` set bits.
+    pub fn weights(&self) -> &[u64] {
+        &self.weights
+    }
+
+    /// Return the numbers of values with every bit position set, least significant first.
+    pub fn positions(&self) -> &[u64] {
+        &self.positions
+    }
+
+    /// Return the number of values with both bit positions `i` and `j` set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is not less than the width of `T`.
+    pub fn pair(&self, i: u32, j: u32) -> u64 {
+        assert!(i < T::BITS && j < T::BITS, "position out of range");
+        self.pairs[(i * T::BITS + j) as usize]
+    }
+
+    /// Return the mean number of set bits.
+    pub fn mean_weight(&self) -> f64 {
+        let total: f64 = self
+            .weights
+            .iter()
+            .enumerate()
+            .map(|(bits, &count)| bits as f64 * count as f64)
+            .sum();
+        total / self.count as f64
+    }
+
+    /// Return the Pearson correlation between bit positions `i` and `j`, or `NaN` if either
+    /// position is constant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is not less than the width of `T`.
+    pub fn correlation(&self, i: u32, j: u32) -> f64 {
+        let n = self.count as f64;
+        let p_i = self.pair(i, i) as f64 / n;
+        let p_j = self.pair(j, j) as f64 / n;
+        let p_ij = self.pair(i, j) as f64 / n;
+        (p_ij - p_i * p_j) / (p_i * (1.0 - p_i) * p_j * (1.0 - p_j)).sqrt()
+    }
+
+    /// Return the largest absolute correlation between two distinct bit positions, ignoring
+    /// constant positions.
+    pub fn max_correlation(&self) -> f64 {
+        let mut max = 0.0f64;
+        for i in 0..T::BITS {
+            for j in (i + 1)..T::BITS {
+                let correlation = self.correlation(i, j);
+                if correlation.is_finite() {
+                    max = max.max(correlation.abs());
+                }
+            }
+        }
+        max
+    }
+}
+
+impl<T> Default for Stats<T>
+where
+    T: Word,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for Stats<T>
+where
+    T: Word,
+{
+    fn extend<I>(&mut self, values: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
+
+impl<T> Display for Stats<T>
+where
+    T: Word,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "values: {}", self.count)?;
+        writeln!(f, "mean weight: {:.3}", self.mean_weight())?;
+        writeln!(f, "weights:")?;
+        for (bits, &count) in self.weights.iter().enumerate().filter(|(_, &count)| count > 0) {
+            writeln!(f, "  {:>3}: {}", bits, count)?;
+        }
+        writeln!(f, "positions:")?;
+        for (position, &count) in self.positions.iter().enumerate() {
+            writeln!(f, "  {:>3}: {:.4}", position, count as f64 / self.count as f64)?;
+        }
+        write!(f, "max correlation: {:.4}", self.max_correlation())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let mut stats = Stats::<u8>::new();
+        stats.extend([0b0000_0011, 0b0000_0101, 0b1000_0001]);
+        assert_eq!(stats.count(), 3);
+        assert_eq!(stats.weights(), [0, 0, 3, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(stats.positions(), [3, 1, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(stats.pair(0, 1), 1);
+        assert_eq!(stats.pair(2, 0), 1);
+        assert_eq!(stats.pair(1, 2), 0);
+        assert!((stats.mean_weight() - 2.0).abs() < 1e-12);
+        assert!(stats.correlation(0, 1).is_nan());
+        assert!((stats.correlation(1, 2) + 0.5).abs() < 1e-12);
+        assert!(stats.to_string().starts_with("values: 3\n"));
+    }
+
+    #[test]
+    fn independent() {
+        let mut rng = rand::thread_rng();
+        let mut stats = Stats::<u16>::new();
+        let class = crate::WeightClass::<u16>::new(8);
+        stats.extend((0..20_000).map(|_| class.choose(&mut rng)));
+        // sampling without replacement makes positions slightly negatively correlated
+        assert!(stats.max_correlation() < 0.1);
+    }
+}
@@ -0,0 +1,130 @@
+//! Stratified sampling across numbers of set bits (ones).
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::stratified::Stratified;
+//!
+//! let values: Vec<u8> = Stratified::uniform(thread_rng(), 100).collect();
+//! assert_eq!(values.len(), 900);
+//! for i in 0..=8 {
+//!     assert_eq!(values.iter().filter(|n| n.count_ones() == i).count(), 100);
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::{Distribution, Standard, Word};
+
+/// An iterator generating a fixed quota of values for every number of set bits, in a random
+/// interleaved order.
+///
+/// Every order of the generated numbers of set bits is equally likely.
+#[derive(Clone, Debug)]
+pub struct Stratified<R, T> {
+    rng: R,
+    quotas: Vec<u64>,
+    remaining: u64,
+    phantom: PhantomData<T>,
+}
+
+impl<R, T> Stratified<R, T>
+where
+    R: Rng,
+    T: Word,
+    Standard: Distribution<T>,
+{
+    /// Create a new iterator generating `quotas[i]` values with `i` set bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quotas` has more entries than possible numbers of set bits of `T`, or the sum
+    /// of `quotas` overflows.
+    pub fn new<I>(rng: R, quotas: I) -> Self
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let quotas: Vec<u64> = quotas.into_iter().collect();
+        assert!(quotas.len() as u32 <= T::BITS + 1, "bits count out of range");
+        let remaining = quotas
+            .iter()
+            .try_fold(0u64, |sum, &quota| sum.checked_add(quota))
+            .expect("quotas sum overflow");
+        Self {
+            rng,
+            quotas,
+            remaining,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new iterator generating `quota` values for every number of set bits from `0` to
+    /// the width of `T`.
+    pub fn uniform(rng: R, quota: u64) -> Self {
+        Self::new(rng, (0..=T::BITS).map(|_| quota))
+    }
+}
+
+impl<R, T> Iterator for Stratified<R, T>
+where
+    R: Rng,
+    Standard: Distribution<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let mut index = self.rng.gen_range(0..self.remaining);
+        let bits = self
+            .quotas
+            .iter()
+            .position(|&quota| {
+                if index < quota {
+                    return true;
+                }
+                index -= quota;
+                false
+            })
+            .expect("quota index out of range");
+        self.quotas[bits] -= 1;
+        self.remaining -= 1;
+        Some(Standard.sample(&mut self.rng, bits as u32))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotas() {
+        let rng = rand::thread_rng();
+        let quotas = [3, 0, 7, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5];
+        let values: Vec<u16> = Stratified::new(rng, quotas).collect();
+        assert_eq!(values.len(), 16);
+        for (i, quota) in quotas.iter().enumerate() {
+            assert_eq!(
+                values.iter().filter(|n| n.count_ones() == i as u32).count() as u64,
+                *quota
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_quotas() {
+        Stratified::<_, u8>::new(rand::thread_rng(), [1; 10]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Reproducible, independent random number generator streams.
+//!
+//! Generators of a single run can be either derived from labels with [`derive()`], or split into
+//! numbered, non-overlapping streams with [`split`] and [`nth`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand_bits::stream::{self, ChaCha12Rng};
+//! use rand_bits::RngBits;
+//!
+//! let mut network: ChaCha12Rng = stream::derive(42, "network");
+//! let mut storage: ChaCha12Rng = stream::derive(42, "storage");
+//! let x: u32 = network.gen_bits(8);
+//! let y: u32 = storage.gen_bits(8);
+//! # let _ = (x, y);
+//! ```
+
+use rand::SeedableRng;
+pub use rand_chacha::ChaCha12Rng;
+
+use crate::hash::{mix64, splitmix64};
+
+/// Return a generator seeded from the master `seed` and a `label` naming its purpose, e.g. the
+/// simulation module using it.
+///
+/// The same seed and label always produce the same generator, while different labels produce
+/// unrelated generators (domain separation). Every 64-bit word of the generator seed is a
+/// separately keyed hash of the master seed, the length of the label and the label itself, so
+/// the whole seed, e.g. the 256-bit key of [`ChaCha12Rng`], depends on the full pair. The
+/// derivation only depends on this crate, so it stays the same across platforms.
+///
+/// The hash is not cryptographic, so the labels must not be chosen by an adversary.
+pub fn derive<R>(seed: u64, label: &str) -> R
+where
+    R: SeedableRng,
+{
+    let mut bytes = R::Seed::default();
+    for (lane, chunk) in bytes.as_mut().chunks_mut(8).enumerate() {
+        let word = hash(lane as u64, seed, label.as_bytes()).to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+    R::from_seed(bytes)
+}
+
+/// Return the hash of `seed` and `label` keyed by the index of the `lane`.
+fn hash(lane: u64, seed: u64, label: &[u8]) -> u64 {
+    let mut key = lane;
+    let key = splitmix64(&mut key);
+    let blocks = label.chunks(8).map(|chunk| {
+        let mut block = [0; 8];
+        block[..chunk.len()].copy_from_slice(chunk);
+        u64::from_le_bytes(block)
+    });
+    // the length keeps labels padded with zeros apart
+    let blocks = [seed, label.len() as u64].into_iter().chain(blocks);
+    let state = blocks.fold(key, |state, block| mix64(state ^ block).wrapping_add(key));
+    mix64(state ^ key)
+}
+
+/// Return the `index`-th stream of the generator seeded with `seed`.
+///
+/// Streams are selected by the ChaCha stream identifier, so distinct streams never overlap. A
+/// stream is the same whether it is created alone or as a part of [`split`], so every thread of
+/// a parallel run can create its own stream and reproduce the outputs of a single-threaded run.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::RngCore;
+/// use rand_bits::stream;
+///
+/// let mut streams = stream::split(7, 4);
+/// let mut third = stream::nth(7, 2);
+/// assert_eq!(streams[2].next_u64(), third.next_u64());
+/// ```
+pub fn nth(seed: u64, index: u64) -> ChaCha12Rng {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    rng.set_stream(index);
+    rng
+}
+
+/// Return the first `count` streams of the generator seeded with `seed`.
+///
+/// See [`nth`] for the properties of the streams.
+pub fn split(seed: u64, count: u64) -> Vec<ChaCha12Rng> {
+    (0..count).map(|index| nth(seed, index)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::RngCore;
+
+    use super::*;
+
+    #[test]
+    fn labels() {
+        let mut a: ChaCha12Rng = derive(1, "a");
+        let mut b: ChaCha12Rng = derive(1, "a");
+        assert_eq!(a.next_u64(), b.next_u64());
+
+        let mut c: ChaCha12Rng = derive(1, "b");
+        let mut d: ChaCha12Rng = derive(2, "a");
+        let first = a.next_u64();
+        assert_ne!(first, c.next_u64());
+        assert_ne!(first, d.next_u64());
+    }
+
+    #[test]
+    fn keys() {
+        let mut keys = HashSet::new();
+        for seed in 0..64 {
+            for label in ["", "a", "a\0", "\0", "network", "storage"] {
+                let key = derive::<ChaCha12Rng>(seed, label).get_seed();
+                // every word of the key is a separate hash
+                let words: HashSet<&[u8]> = key.chunks(8).collect();
+                assert_eq!(words.len(), 4);
+                assert!(keys.insert(key));
+            }
+        }
+    }
+
+    #[test]
+    fn streams() {
+        let mut streams = split(3, 8);
+        let outputs: Vec<u64> = streams.iter_mut().map(|rng| rng.next_u64()).collect();
+        for (index, output) in outputs.iter().enumerate() {
+            assert_eq!(nth(3, index as u64).next_u64(), *output);
+            assert_eq!(outputs.iter().filter(|&other| other == output).count(), 1);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Sequences with a controlled number of toggling bits between consecutive values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::switching::Switching;
+//!
+//! let words: Vec<u32> = Switching::new(thread_rng(), 5).take(100).collect();
+//! for w in words.windows(2) {
+//!     assert_eq!((w[0] ^ w[1]).count_ones(), 5);
+//! }
+//! ```
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::{select, Word};
+
+/// An infinite sequence of values where a controlled number of bits toggles between every two
+/// consecutive values, e.g. for dynamic power estimation or crosstalk stress patterns.
+///
+/// The first value is uniformly chosen. Every following one is its predecessor with uniformly
+/// chosen bits toggled, independently of the number of set bits of the values.
+#[derive(Clone, Debug)]
+pub struct Switching<R, T> {
+    rng: R,
+    value: Option<u128>,
+    toggles: u32,
+    at_most: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<R, T> Switching<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    /// Create a new sequence toggling exactly `toggles` bits per step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `toggles` is greater than the width of `T`.
+    pub fn new(rng: R, toggles: u32) -> Self {
+        assert!(toggles <= T::BITS, "bits count out of range");
+        Self {
+            rng,
+            value: None,
+            toggles,
+            at_most: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new sequence toggling at most `toggles` bits per step, where every step is
+    /// uniformly chosen among all steps toggling that many bits or less.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `toggles` is greater than the width of `T`.
+    pub fn at_most(rng: R, toggles: u32) -> Self {
+        Self {
+            at_most: true,
+            ..Self::new(rng, toggles)
+        }
+    }
+
+    /// Return the number of bits to toggle in the next step.
+    fn toggles(&mut self) -> u32 {
+        if !self.at_most {
+            return self.toggles;
+        }
+        // the steps toggling `j` bits number `C(T::BITS, j)`
+        let total: u128 = (0..=self.toggles).map(|j| binomial(T::BITS, j)).sum();
+        let mut rank = self.rng.gen_range(0..total);
+        for j in 0..self.toggles {
+            let count = binomial(T::BITS, j);
+            if rank < count {
+                return j;
+            }
+            rank -= count;
+        }
+        self.toggles
+    }
+}
+
+impl<R, T> Iterator for Switching<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mask = select::ones(T::BITS);
+        let value = match self.value {
+            None => self.rng.gen::<u128>() & mask,
+            // every value is within reach, so the step is a uniformly chosen value
+            Some(_) if self.at_most && self.toggles == T::BITS => self.rng.gen::<u128>() & mask,
+            Some(value) => {
+                let toggles = self.toggles();
+                value ^ select::sample(&mut self.rng, mask, toggles)
+            },
+        };
+        self.value = Some(value);
+        Some(T::from_u128(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        for i in 0..=u64::BITS {
+            let values: Vec<u64> = Switching::new(rand::thread_rng(), i).take(20).collect();
+            assert!(values.windows(2).all(|w| (w[0] ^ w[1]).count_ones() == i));
+        }
+    }
+
+    #[test]
+    fn at_most() {
+        let values: Vec<u8> = Switching::at_most(rand::thread_rng(), 2).take(10_000).collect();
+        let mut counts = [0u32; 3];
+        for w in values.windows(2) {
+            counts[(w[0] ^ w[1]).count_ones() as usize] += 1;
+        }
+        // 1, 8 and 28 of the 37 steps toggle 0, 1 and 2 bits
+        assert!(counts[0] < 500);
+        assert!((1_800..2_500).contains(&counts[1]));
+        assert!((7_100..8_000).contains(&counts[2]));
+        let values: Vec<u128> = Switching::at_most(rand::thread_rng(), 128).take(10).collect();
+        assert_eq!(values.len(), 10);
+    }
+}
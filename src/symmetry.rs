@@ -0,0 +1,107 @@
+//! Values invariant under a reordering of their bits.
+
+use rand::Rng;
+
+use crate::combination::binomial;
+use crate::{paranoid, select};
+
+/// A reordering of bits, under which generated values stay unchanged.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    /// Reversing the order of all bits, i.e. the bit pattern is a palindrome.
+    BitReversal,
+    /// Reversing the order of bytes, i.e. the byte sequence is a palindrome.
+    ByteReversal,
+}
+
+impl Symmetry {
+    /// Apply the reordering to the `width`-bit `value`.
+    fn apply(self, value: u128, width: u32) -> u128 {
+        let shift = u128::BITS - width;
+        match self {
+            Self::BitReversal => value.reverse_bits() >> shift,
+            Self::ByteReversal => value.swap_bytes() >> shift,
+        }
+    }
+}
+
+/// Return a uniformly chosen `width`-bit value with `bits` set bits, invariant under `symmetry`.
+///
+/// Positions fixed by the reordering contribute one set bit each and the remaining ones are
+/// mirrored in pairs, so the split between the two kinds is drawn with weights proportional to
+/// the numbers of values they produce.
+pub(crate) fn sample<R>(rng: &mut R, width: u32, bits: u32, symmetry: Symmetry) -> u128
+where
+    R: Rng + ?Sized,
+{
+    let mut fixed = 0u128;
+    let mut pairs = 0u128;
+    for position in 0..width {
+        let image = symmetry.apply(1 << position, width);
+        if image == 1 << position {
+            fixed |= image;
+        } else if image > 1 << position {
+            pairs |= 1 << position;
+        }
+    }
+
+    let counts: Vec<(u32, u128)> = (0..=bits.min(fixed.count_ones()))
+        .filter(|single| (bits - single) % 2 == 0)
+        .map(|single| {
+            let count = binomial(fixed.count_ones(), single) * binomial(pairs.count_ones(), (bits - single) / 2);
+            (single, count)
+        })
+        .collect();
+    let total: u128 = counts.iter().map(|(_, count)| count).sum();
+    assert!(total > 0, "bits count out of range");
+
+    let mut index = rng.gen_range(0..total);
+    for (single, count) in counts {
+        if index < count {
+            let halves = select::sample(rng, pairs, (bits - single) / 2);
+            let value = select::sample(rng, fixed, single) | halves | symmetry.apply(halves, width);
+            paranoid::weight("gen_bits_symmetric", value, bits, select::ones(width));
+            paranoid::constraint("gen_bits_symmetric", symmetry.apply(value, width) == value, || {
+                format!("value {:#b} changes under {:?}", value, symmetry)
+            });
+            return value;
+        }
+        index -= count;
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_reversal() {
+        let mut rng = rand::thread_rng();
+        for i in (0..=u32::BITS).step_by(2) {
+            let n = sample(&mut rng, u32::BITS, i, Symmetry::BitReversal) as u32;
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(n.reverse_bits(), n);
+        }
+    }
+
+    #[test]
+    fn byte_reversal() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let n = sample(&mut rng, u8::BITS, i, Symmetry::ByteReversal) as u8;
+            assert_eq!(n.count_ones(), i);
+        }
+        for i in (0..=u64::BITS).step_by(2) {
+            let n = sample(&mut rng, u64::BITS, i, Symmetry::ByteReversal) as u64;
+            assert_eq!(n.count_ones(), i);
+            assert_eq!(n.swap_bytes(), n);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn odd() {
+        sample(&mut rand::thread_rng(), u16::BITS, 3, Symmetry::BitReversal);
+    }
+}
@@ -0,0 +1,183 @@
+//! Exhaustive verification of generators for downstream test suites, enabled by the
+//! `test-util` feature.
+//!
+//! The support of a distribution of `u8` or `u16` values with a fixed number of set bits is
+//! small enough to enumerate, so every value of it can be checked to appear with the expected
+//! frequency.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::{testing, RngBits};
+//!
+//! let mut rng = thread_rng();
+//! testing::assert_uniform::<u8, _>(3, 200, 6.0, || rng.gen_bits(3));
+//! ```
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use rand::Rng;
+
+use crate::{Distribution, Standard, WeightClass, Word};
+
+/// A value generated with a frequency too far from the expected one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deviation {
+    /// The value.
+    pub value: u128,
+    /// The expected number of occurrences.
+    pub expected: f64,
+    /// The observed number of occurrences.
+    pub observed: u64,
+}
+
+impl Display for Deviation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value {:#b} generated {} times, expected {:.1}",
+            self.value, self.observed, self.expected
+        )
+    }
+}
+
+impl error::Error for Deviation {}
+
+/// Check that `sample` generates every value with `bits` set bits with the same frequency, and
+/// no other value.
+///
+/// The generator is called `per_value` times for every value of the support. Every count must
+/// lie within `sigmas` standard deviations of the expected count; with thousands of values in
+/// the support, `6.0` keeps false alarms negligible.
+///
+/// # Errors
+///
+/// Returns the first [`Deviation`] found, reporting values outside the support as expected
+/// zero times.
+///
+/// # Panics
+///
+/// Panics if `T` is wider than 16 bits or `bits` is greater than its width.
+pub fn verify_uniform<T, F>(bits: u32, per_value: u64, sigmas: f64, mut sample: F) -> Result<(), Deviation>
+where
+    T: Word,
+    F: FnMut() -> T,
+{
+    assert!(T::BITS <= 16, "support too large to enumerate");
+    let class = WeightClass::<T>::new(bits);
+    let samples = class.len() as u64 * per_value;
+    let mut counts = HashMap::new();
+    for _ in 0..samples {
+        *counts.entry(sample()).or_insert(0u64) += 1;
+    }
+
+    if let Some((value, &observed)) = counts.iter().find(|(value, _)| !class.contains(**value)) {
+        return Err(Deviation {
+            value: value.into_u128(),
+            expected: 0.0,
+            observed,
+        });
+    }
+    // the count of every value is binomial with probability 1 / class.len()
+    let expected = per_value as f64;
+    let deviation = (expected * (1.0 - 1.0 / class.len() as f64)).sqrt();
+    for value in &class {
+        let observed = counts.get(&value).copied().unwrap_or_default();
+        if (observed as f64 - expected).abs() > sigmas * deviation {
+            return Err(Deviation {
+                value: value.into_u128(),
+                expected,
+                observed,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Assert that `sample` generates every value with `bits` set bits with the same frequency, and
+/// no other value.
+///
+/// See [`verify_uniform`].
+///
+/// # Panics
+///
+/// Panics with the deviation found, if any.
+pub fn assert_uniform<T, F>(bits: u32, per_value: u64, sigmas: f64, sample: F)
+where
+    T: Word,
+    F: FnMut() -> T,
+{
+    if let Err(deviation) = verify_uniform(bits, per_value, sigmas, sample) {
+        panic!("non-uniform generator with {} set bits: {}", bits, deviation);
+    }
+}
+
+/// Assert that the [`Standard`] distribution of `T` is uniform for every number of set bits.
+///
+/// Only `u8` values are chosen uniformly by [`Standard`] (see [`Algorithm::Table`]), so wider
+/// types are rejected; check them with [`assert_uniform`] and a uniform generator such as
+/// [`WeightClass::choose`].
+///
+/// # Panics
+///
+/// Panics if `T` is wider than 8 bits or the distribution deviates.
+///
+/// [`Algorithm::Table`]: crate::Algorithm::Table
+pub fn assert_standard<T, R>(rng: &mut R, per_value: u64)
+where
+    T: Word,
+    Standard: Distribution<T>,
+    R: Rng + ?Sized,
+{
+    assert!(T::BITS <= u8::BITS, "Standard is uniform only for u8");
+    for bits in 0..=T::BITS {
+        assert_uniform::<T, _>(bits, per_value, 6.0, || Standard.sample(rng, bits));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard() {
+        assert_standard::<u8, _>(&mut rand::thread_rng(), 500);
+    }
+
+    #[test]
+    #[should_panic(expected = "Standard is uniform only for u8")]
+    fn standard_u16() {
+        assert_standard::<u16, _>(&mut rand::thread_rng(), 500);
+    }
+
+    #[test]
+    fn u16() {
+        let mut rng = rand::thread_rng();
+        for bits in [0, 1, 2, 14, 15, 16] {
+            let class = WeightClass::<u16>::new(bits);
+            assert_uniform::<u16, _>(bits, 500, 6.0, || class.choose(&mut rng));
+        }
+        // Standard splits the bits between the bytes uniformly, so 0b1100 with both bits in the
+        // low byte comes up about 1.4 times as often as it should
+        let result = verify_uniform::<u16, _>(2, 1000, 6.0, || Standard.sample(&mut rng, 2));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn detected() {
+        let mut rng = rand::thread_rng();
+        let result = verify_uniform::<u8, _>(2, 500, 6.0, || rng.gen_range(0..8u8) | 0x80);
+        assert_eq!(result.unwrap_err().expected, 0.0);
+        // the high bit is set twice as often as it should be
+        let result = verify_uniform::<u8, _>(2, 500, 6.0, || {
+            match rng.gen_bool(0.5) {
+                true => 0x80 | (1 << rng.gen_range(0..7)),
+                false => WeightClass::new(2).choose(&mut rng),
+            }
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Infinite sampling without repeated values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::unique::{Filter, Unique};
+//!
+//! let mut values: Vec<u8> = Unique::new(thread_rng(), 2, Filter::Exact).collect();
+//! assert_eq!(values.len(), 28);
+//! values.sort();
+//! values.dedup();
+//! assert_eq!(values.len(), 28);
+//! ```
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::hash::mix64;
+use crate::{Error, WeightClass, Word};
+
+/// The structure used to remember already generated values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Filter {
+    /// Remember every value exactly. Memory grows with the number of generated values.
+    Exact,
+    /// Remember values in a Bloom filter of `bits` bits using `hashes` hash functions. Memory is
+    /// bounded, at the cost of skipping some values never generated before (false positives).
+    Bloom {
+        /// The number of bits of the filter.
+        bits: usize,
+        /// The number of hash functions.
+        hashes: u32,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Seen<T> {
+    Exact(HashSet<T>),
+    Bloom {
+        words: Vec<u64>,
+        bits: u64,
+        hashes: u32,
+        set: u64,
+    },
+}
+
+impl<T> Seen<T>
+where
+    T: Word,
+{
+    fn new(filter: Filter) -> Self {
+        match filter {
+            Filter::Exact => Self::Exact(HashSet::new()),
+            Filter::Bloom { bits, hashes } => {
+                assert!(bits > 0 && hashes > 0, "empty Bloom filter");
+                Self::Bloom {
+                    words: vec![0; (bits + 63) / 64],
+                    bits: bits as u64,
+                    hashes,
+                    set: 0,
+                }
+            },
+        }
+    }
+
+    /// Remember `value`, returning `true` if it was not remembered before.
+    fn insert(&mut self, value: T) -> bool {
+        match self {
+            Self::Exact(set) => set.insert(value),
+            Self::Bloom {
+                words,
+                bits,
+                hashes,
+                set,
+            } => {
+                // double hashing of the two halves of the value
+                let value = value.into_u128();
+                let h1 = mix64(value as u64 ^ mix64((value >> 64) as u64));
+                let h2 = mix64(h1 ^ 0x9E37_79B9_7F4A_7C15) | 1;
+                let mut inserted = false;
+                for i in 0..u64::from(*hashes) {
+                    let bit = h1.wrapping_add(i.wrapping_mul(h2)) % *bits;
+                    let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+                    if words[word] & mask == 0 {
+                        words[word] |= mask;
+                        *set += 1;
+                        inserted = true;
+                    }
+                }
+                inserted
+            },
+        }
+    }
+
+    /// Return `true` if no value can be remembered anymore.
+    fn is_full(&self) -> bool {
+        match self {
+            Self::Exact(_) => false,
+            Self::Bloom { bits, set, .. } => set == bits,
+        }
+    }
+}
+
+/// An iterator generating values with a fixed number of set bits, skipping the ones generated
+/// before.
+///
+/// The iterator ends once every value of the class has been generated, or the Bloom filter is
+/// saturated and rejects every value. As a nearly saturated Bloom filter rejects almost every
+/// value, it also ends after [`Unique::MAX_REJECTIONS`] values in a row are rejected, which
+/// [`Unique::try_next`] tells apart. An exact filter rejects values only while it remembers them,
+/// so it takes at most as many draws per value on average as there are values in the class.
+#[derive(Clone, Debug)]
+pub struct Unique<R, T> {
+    rng: R,
+    class: WeightClass<T>,
+    seen: Seen<T>,
+    count: u128,
+    exhausted: bool,
+}
+
+impl<R, T> Unique<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    /// Create a new iterator generating values with `bits` set bits, remembering them with
+    /// `filter`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than the width of `T`, or the Bloom filter has no bits or
+    /// hash functions.
+    pub fn new(rng: R, bits: u32, filter: Filter) -> Self {
+        Self {
+            rng,
+            class: WeightClass::new(bits),
+            seen: Seen::new(filter),
+            count: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Generate the next value, or return `None` once no value is left.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AttemptsExhausted`] if the Bloom filter rejected
+    /// [`Unique::MAX_REJECTIONS`] values in a row, now or in an earlier call.
+    pub fn try_next(&mut self) -> Result<Option<T>, Error> {
+        if self.exhausted {
+            return Err(Error::AttemptsExhausted);
+        }
+        let mut rejections = 0;
+        while self.count < self.class.len() && !self.seen.is_full() {
+            let value = self.class.choose(&mut self.rng);
+            if self.seen.insert(value) {
+                self.count += 1;
+                return Ok(Some(value));
+            }
+            rejections += 1;
+            if rejections == Self::MAX_REJECTIONS && matches!(self.seen, Seen::Bloom { .. }) {
+                self.exhausted = true;
+                return Err(Error::AttemptsExhausted);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R, T> Unique<R, T> {
+    /// The number of values in a row a Bloom filter may reject before the iterator ends.
+    pub const MAX_REJECTIONS: u32 = 1024;
+}
+
+impl<R, T> Iterator for Unique<R, T>
+where
+    R: Rng,
+    T: Word,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.try_next().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        let rng = rand::thread_rng();
+        for i in 0..=u8::BITS {
+            let values: Vec<u8> = Unique::new(rng.clone(), i, Filter::Exact).collect();
+            let unique: HashSet<u8> = values.iter().copied().collect();
+            assert_eq!(values.len(), unique.len());
+            assert_eq!(values.len() as u128, WeightClass::<u8>::new(i).len());
+        }
+    }
+
+    #[test]
+    fn bloom() {
+        let filter = Filter::Bloom {
+            bits: 1 << 12,
+            hashes: 3,
+        };
+        let values: Vec<u64> = Unique::new(rand::thread_rng(), 32, filter).take(200).collect();
+        let unique: HashSet<u64> = values.iter().copied().collect();
+        assert_eq!(values.len(), 200);
+        assert_eq!(unique.len(), 200);
+
+        // a saturated filter ends the iterator
+        let filter = Filter::Bloom { bits: 8, hashes: 2 };
+        assert!(Unique::<_, u64>::new(rand::thread_rng(), 32, filter).count() <= 8);
+    }
+
+    #[test]
+    fn saturated() {
+        // with u bits of 2^14 unset, a run of 1024 rejections is likely once u is about 16,
+        // long before the filter is full
+        let filter = Filter::Bloom {
+            bits: 1 << 14,
+            hashes: 1,
+        };
+        let mut unique = Unique::<_, u64>::new(rand::thread_rng(), 32, filter);
+        let result = loop {
+            match unique.try_next() {
+                Ok(Some(_)) => continue,
+                result => break result,
+            }
+        };
+        assert_eq!(result, Err(Error::AttemptsExhausted));
+        assert_eq!(unique.try_next(), Err(Error::AttemptsExhausted));
+        assert_eq!(unique.next(), None);
+    }
+}
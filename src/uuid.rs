@@ -0,0 +1,66 @@
+//! UUID-shaped 128-bit values with a fixed number of set bits (ones).
+//!
+//! Values are laid out as in [RFC 9562](https://www.rfc-editor.org/rfc/rfc9562), i.e. the most
+//! significant byte of the `u128` is the first byte of the UUID.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::uuid;
+//!
+//! let x = uuid::gen_v4(&mut thread_rng(), 10);
+//! assert_eq!(x & uuid::VERSION_MASK, uuid::VERSION_4);
+//! assert_eq!(x.count_ones(), 12);
+//! ```
+
+use rand::Rng;
+
+use crate::select;
+
+/// The mask of the version field.
+pub const VERSION_MASK: u128 = 0xF << 76;
+
+/// The version field of a random (version 4) UUID.
+pub const VERSION_4: u128 = 0x4 << 76;
+
+/// The mask of the variant field.
+pub const VARIANT_MASK: u128 = 0x3 << 62;
+
+/// The variant field of an RFC 9562 UUID.
+pub const VARIANT_RFC: u128 = 0x2 << 62;
+
+/// The number of random bits in a version 4 UUID.
+pub const RANDOM_BITS: u32 = 122;
+
+/// Return a random version 4 UUID with `bits` of its random bits set.
+///
+/// The version and variant fields always carry one set bit each, so the returned value has
+/// `bits + 2` set bits in total.
+///
+/// # Panics
+///
+/// Panics if `bits` is greater than [`RANDOM_BITS`].
+pub fn gen_v4<R>(rng: &mut R, bits: u32) -> u128
+where
+    R: Rng + ?Sized,
+{
+    let mask = !(VERSION_MASK | VARIANT_MASK);
+    select::sample(rng, mask, bits) | VERSION_4 | VARIANT_RFC
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v4() {
+        let mut rng = rand::thread_rng();
+        for i in 0..=RANDOM_BITS {
+            let n = gen_v4(&mut rng, i);
+            assert_eq!(n & VERSION_MASK, VERSION_4);
+            assert_eq!(n & VARIANT_MASK, VARIANT_RFC);
+            assert_eq!(n.count_ones(), i + 2);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Verification of received data against the constraints of the generators.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::frame::Frame;
+//! use rand_bits::verify::{Blocks, Kind, Violation};
+//!
+//! let frame = Frame::new(16).field(0, &[0xAA]).payload_bits(40..=40);
+//! let mut buffer = Vec::new();
+//! frame.write(&mut thread_rng(), &mut buffer, 4).unwrap();
+//! buffer[35] ^= 0x01;
+//!
+//! let blocks = Blocks::new(16).segment(0..1, 4..=4).segment(1..16, 40..=40);
+//! let violations = blocks.check(&buffer);
+//! assert_eq!(violations.len(), 1);
+//! assert_eq!(violations[0].offset, 32);
+//! assert!(matches!(violations[0].kind, Kind::Segment { index: 1, .. }));
+//! ```
+
+use std::io::{self, ErrorKind, Read};
+use std::ops::{Range, RangeInclusive};
+
+use crate::Word;
+
+/// A violated constraint of a block.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Kind {
+    /// The data ends with an incomplete block of `len` bytes.
+    Truncated {
+        /// The number of bytes of the incomplete block.
+        len: usize,
+    },
+    /// The block has `bits` set bits, out of the allowed range.
+    Weight {
+        /// The number of set bits of the block.
+        bits: u32,
+    },
+    /// The block has `bits` set bits outside the mask.
+    Mask {
+        /// The number of set bits outside the mask.
+        bits: u32,
+    },
+    /// The segment at `index` has `bits` set bits, out of its allowed range.
+    Segment {
+        /// The index of the segment in the order of addition.
+        index: usize,
+        /// The number of set bits of the segment.
+        bits: u32,
+    },
+}
+
+/// A violation of the constraints by the block at `offset`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Violation {
+    /// The offset of the first byte of the block.
+    pub offset: usize,
+    /// The violated constraint.
+    pub kind: Kind,
+}
+
+/// Constraints of the fixed-size blocks data is made of.
+///
+/// Every block may be constrained by the total number of its set bits, by a mask of the
+/// positions allowed to be set, and by the number of set bits of segments, i.e. byte ranges of
+/// the block, like the header fields and the payload of a [`Frame`](crate::frame::Frame).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Blocks {
+    len: usize,
+    bits: Option<RangeInclusive<u32>>,
+    mask: Option<Vec<u8>>,
+    segments: Vec<(Range<usize>, RangeInclusive<u32>)>,
+}
+
+impl Blocks {
+    /// Create new constraints of blocks of `len` bytes, accepting every block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero.
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "block length is zero");
+        Self {
+            len,
+            bits: None,
+            mask: None,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Require the number of set bits of every block to be within `bits`.
+    pub fn bits(mut self, bits: RangeInclusive<u32>) -> Self {
+        self.bits = Some(bits);
+        self
+    }
+
+    /// Require every set bit of a block to be set in `mask` as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `mask` differs from the length of blocks.
+    pub fn mask(mut self, mask: &[u8]) -> Self {
+        assert_eq!(mask.len(), self.len, "mask length mismatch");
+        self.mask = Some(mask.to_vec());
+        self
+    }
+
+    /// Require the number of set bits of the bytes of every block within `range` to be within
+    /// `bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment does not fit in the block.
+    pub fn segment(mut self, range: Range<usize>, bits: RangeInclusive<u32>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "segment out of range"
+        );
+        self.segments.push((range, bits));
+        self
+    }
+
+    /// Return the length of blocks.
+    pub fn block_len(&self) -> usize {
+        self.len
+    }
+
+    /// Check all blocks of `buffer` and return the violations in the order of offsets.
+    pub fn check(&self, buffer: &[u8]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.check_at(buffer, 0, &mut violations);
+        violations
+    }
+
+    /// Check all blocks read from `reader` and return the violations in the order of offsets.
+    pub fn check_reader<R>(&self, mut reader: R) -> io::Result<Vec<Violation>>
+    where
+        R: Read,
+    {
+        let mut violations = Vec::new();
+        let mut block = vec![0; self.len];
+        let mut offset = 0;
+        loop {
+            let mut filled = 0;
+            while filled < self.len {
+                match reader.read(&mut block[filled..]) {
+                    Ok(0) => break,
+                    Ok(read) => filled += read,
+                    Err(error) if error.kind() == ErrorKind::Interrupted => {},
+                    Err(error) => return Err(error),
+                }
+            }
+            self.check_at(&block[..filled], offset, &mut violations);
+            if filled < self.len {
+                return Ok(violations);
+            }
+            offset += self.len;
+        }
+    }
+
+    fn check_at(&self, buffer: &[u8], offset: usize, violations: &mut Vec<Violation>) {
+        let mut blocks = buffer.chunks_exact(self.len);
+        for (index, block) in blocks.by_ref().enumerate() {
+            let offset = offset + index * self.len;
+            violations.extend(self.violations(block).map(|kind| Violation { offset, kind }));
+        }
+        let rest = blocks.remainder();
+        if !rest.is_empty() {
+            let offset = offset + buffer.len() - rest.len();
+            let kind = Kind::Truncated { len: rest.len() };
+            violations.push(Violation { offset, kind });
+        }
+    }
+
+    fn violations<'a>(&'a self, block: &'a [u8]) -> impl Iterator<Item = Kind> + 'a {
+        let bits = ones(block);
+        let weight = match &self.bits {
+            Some(range) if !range.contains(&bits) => Some(Kind::Weight { bits }),
+            _ => None,
+        };
+        let outside = match &self.mask {
+            Some(mask) => {
+                block
+                    .iter()
+                    .zip(mask)
+                    .map(|(byte, mask)| (byte & !mask).count_ones())
+                    .sum()
+            },
+            None => 0,
+        };
+        let mask = match outside {
+            0 => None,
+            bits => Some(Kind::Mask { bits }),
+        };
+        let segments = self
+            .segments
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, (range, bits))| {
+                let ones = ones(&block[range.clone()]);
+                match bits.contains(&ones) {
+                    true => None,
+                    false => Some(Kind::Segment { index, bits: ones }),
+                }
+            });
+        weight.into_iter().chain(mask).chain(segments)
+    }
+}
+
+/// Return the indices of the `values` without exactly `bits` set bits.
+///
+/// # Example
+///
+/// ```rust
+/// use rand_bits::verify;
+///
+/// assert_eq!(verify::words(&[0b11u8, 0b111, 0b101, 0], 2), [1, 3]);
+/// ```
+pub fn words<T>(values: &[T], bits: u32) -> Vec<usize>
+where
+    T: Word,
+{
+    let violating = values.iter().enumerate();
+    let violating = violating.filter(|(_, value)| value.into_u128().count_ones() != bits);
+    violating.map(|(index, _)| index).collect()
+}
+
+fn ones(bytes: &[u8]) -> u32 {
+    bytes.iter().map(|byte| byte.count_ones()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::RngBits;
+
+    #[test]
+    fn violations() {
+        let blocks = Blocks::new(4)
+            .bits(8..=8)
+            .mask(&[0xFF, 0xFF, 0xFF, 0x0F])
+            .segment(0..2, 4..=4);
+        let buffer = [
+            0x0F, 0x00, 0x0F, 0x00, // valid
+            0xFF, 0x00, 0x00, 0x00, // segment
+            0x0F, 0x00, 0x00, 0xF0, // mask
+            0x0F, 0x00, 0x00, 0x01, // weight
+            0x0F, 0x00, // truncated
+        ];
+        let expected = [
+            Violation {
+                offset: 4,
+                kind: Kind::Segment { index: 0, bits: 8 },
+            },
+            Violation {
+                offset: 8,
+                kind: Kind::Mask { bits: 4 },
+            },
+            Violation {
+                offset: 12,
+                kind: Kind::Weight { bits: 5 },
+            },
+            Violation {
+                offset: 16,
+                kind: Kind::Truncated { len: 2 },
+            },
+        ];
+        assert_eq!(blocks.check(&buffer), expected);
+        assert_eq!(blocks.check_reader(&buffer[..]).unwrap(), expected);
+    }
+
+    #[test]
+    fn generated() {
+        let mut rng = rand::thread_rng();
+        let blocks = Blocks::new(8).bits(24..=24);
+        let mut buffer = Vec::new();
+        for _ in 0..100 {
+            buffer.extend_from_slice(&rng.gen_bits::<u64>(24).to_le_bytes());
+        }
+        assert!(blocks.check(&buffer).is_empty());
+        let index = rng.gen_range(0..buffer.len());
+        buffer[index] ^= 0x80;
+        let violations = blocks.check_reader(&buffer[..]).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].offset, index / 8 * 8);
+    }
+}
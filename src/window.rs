@@ -0,0 +1,134 @@
+//! Bit streams with a bounded number of set bits (ones) in every sliding window.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rand::thread_rng;
+//! use rand_bits::window::Windowed;
+//!
+//! let bits: Vec<bool> = Windowed::new(thread_rng(), 8, 2..=3).take(1000).collect();
+//! for window in bits.windows(8) {
+//!     let ones = window.iter().filter(|&&bit| bit).count();
+//!     assert!((2..=3).contains(&ones));
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::paranoid;
+
+/// An infinite stream of bits where every window of `width` consecutive bits contains a number
+/// of set bits within the chosen range.
+///
+/// Every bit is chosen with a fair coin flip unless only one of its values keeps the stream
+/// within the bounds. Such a choice always exists, so the stream never gets stuck.
+#[derive(Clone, Debug)]
+pub struct Windowed<R> {
+    rng: R,
+    width: usize,
+    bounds: RangeInclusive<u32>,
+    history: VecDeque<bool>,
+    ones: u32,
+}
+
+impl<R> Windowed<R>
+where
+    R: Rng,
+{
+    /// Create a new stream with windows of `width` bits, each one with a number of set bits
+    /// within `bounds`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero, `bounds` is empty or its end is greater than `width`.
+    pub fn new(rng: R, width: u32, bounds: RangeInclusive<u32>) -> Self {
+        assert!(width > 0, "window width is zero");
+        assert!(!bounds.is_empty() && *bounds.end() <= width, "bits count out of range");
+        Self {
+            rng,
+            width: width as usize,
+            bounds,
+            history: VecDeque::with_capacity(width as usize),
+            ones: 0,
+        }
+    }
+
+    /// Fill `dest` with the following bits of the stream, least significant bit first.
+    pub fn fill(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            *byte = 0;
+            for i in 0..u8::BITS {
+                if self.next_bit() {
+                    *byte |= 1 << i;
+                }
+            }
+        }
+    }
+
+    fn next_bit(&mut self) -> bool {
+        // number of bits still missing to complete the window ending with the next bit
+        let missing = (self.width - self.history.len() - 1) as u32;
+        let allowed = |ones: u32| ones <= *self.bounds.end() && ones + missing >= *self.bounds.start();
+        let bit = match (allowed(self.ones), allowed(self.ones + 1)) {
+            (true, true) => self.rng.gen(),
+            (allowed_zero, _) => !allowed_zero,
+        };
+
+        self.history.push_back(bit);
+        self.ones += u32::from(bit);
+        if self.history.len() == self.width {
+            let ones = self.ones;
+            paranoid::constraint("Windowed", self.bounds.contains(&ones), || {
+                format!("window with {} set bits outside {:?}", ones, self.bounds)
+            });
+            let oldest = self.history.pop_front().unwrap_or_default();
+            self.ones -= u32::from(oldest);
+        }
+        bit
+    }
+}
+
+impl<R> Iterator for Windowed<R>
+where
+    R: Rng,
+{
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        Some(self.next_bit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        let rng = rand::thread_rng();
+        for width in 1..=12 {
+            for start in 0..=width {
+                for end in start..=width {
+                    let bits: Vec<bool> = Windowed::new(rng.clone(), width, start..=end).take(256).collect();
+                    for window in bits.windows(width as usize) {
+                        let ones = window.iter().filter(|&&bit| bit).count() as u32;
+                        assert!((start..=end).contains(&ones));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fill() {
+        let mut stream = Windowed::new(rand::thread_rng(), 16, 4..=4);
+        let mut bytes = [0u8; 64];
+        stream.fill(&mut bytes);
+        for window in bytes.chunks(2) {
+            assert_eq!(window[0].count_ones() + window[1].count_ones(), 4);
+        }
+    }
+}
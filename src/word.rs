@@ -0,0 +1,51 @@
+//! Unsigned integer types supported by the generators.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// An unsigned integer type that can be generated with a fixed number of set bits (ones).
+///
+/// This trait is sealed and implemented for `u8`, `u16`, `u32`, `u64` and `u128`.
+pub trait Word: Copy + Debug + Default + Eq + Hash + Ord + private::Sealed {
+    /// The size of this integer type in bits.
+    const BITS: u32;
+
+    /// Return the number of ones in the binary representation of `self`.
+    fn count_ones(self) -> u32;
+
+    /// Convert `value` into `Self`, truncating the high bits.
+    fn from_u128(value: u128) -> Self;
+
+    /// Convert `self` into `u128`.
+    fn into_u128(self) -> u128;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl Word for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                fn count_ones(self) -> u32 {
+                    <$t>::count_ones(self)
+                }
+
+                fn from_u128(value: u128) -> Self {
+                    value as $t
+                }
+
+                fn into_u128(self) -> u128 {
+                    self as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u8, u16, u32, u64, u128);